@@ -0,0 +1,84 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use super::position::{Direction, Position};
+
+///everything a player can do on their turn, in the order it gets recorded for replays
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Move(Position),
+    ///index into the current wizard's spells
+    Cast(usize, SpellTarget),
+    EndTurn,
+    ///picks a starting tile before the first turn
+    Place(Position),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpellTarget {
+    None,
+    Position(Position),
+    Direction(Direction),
+}
+
+#[derive(Debug, Clone)]
+pub struct IllegalAction {
+    pub action: Action,
+    pub reason: &'static str,
+}
+
+impl Display for IllegalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is illegal: {}", self.action, self.reason)
+    }
+}
+
+///the text form bots use: `move <x> <y>`, `cast <spell> none`, `cast <spell> at <x> <y>`,
+///`cast <spell> <up|down|left|right>`, `end` or `place <x> <y>`, where `<spell>` indexes the wizard's spells
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Move(pos) => write!(f, "move {} {}", pos.x, pos.y),
+            Action::Cast(i, SpellTarget::None) => write!(f, "cast {i} none"),
+            Action::Cast(i, SpellTarget::Position(pos)) => {
+                write!(f, "cast {i} at {} {}", pos.x, pos.y)
+            }
+            Action::Cast(i, SpellTarget::Direction(dir)) => {
+                write!(f, "cast {i} {}", format!("{dir:?}").to_lowercase())
+            }
+            Action::EndTurn => write!(f, "end"),
+            Action::Place(pos) => write!(f, "place {} {}", pos.x, pos.y),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<usize>()
+                .map_err(|_| format!("expected a number, got `{word}`"))
+        };
+        match words.as_slice() {
+            ["move", x, y] => Ok(Action::Move((number(x)?, number(y)?).into())),
+            ["cast", i, "none"] => Ok(Action::Cast(number(i)?, SpellTarget::None)),
+            ["cast", i, "at", x, y] => Ok(Action::Cast(
+                number(i)?,
+                SpellTarget::Position((number(x)?, number(y)?).into()),
+            )),
+            ["cast", i, dir] => {
+                let dir = Direction::iter()
+                    .find(|d| format!("{d:?}").eq_ignore_ascii_case(dir))
+                    .ok_or_else(|| format!("`{dir}` isn't a direction"))?;
+                Ok(Action::Cast(number(i)?, SpellTarget::Direction(dir)))
+            }
+            ["end"] => Ok(Action::EndTurn),
+            ["place", x, y] => Ok(Action::Place((number(x)?, number(y)?).into())),
+            _ => Err(format!("`{s}` isn't an action")),
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use super::{
+    action::{Action, IllegalAction},
+    ai, Battle, Seat,
+};
+
+///how many illegal actions in a row an agent gets before its turn is ended for it
+pub const MAX_ILLEGAL_ACTIONS: usize = 3;
+
+pub const BOT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const PROTOCOL_VERSION: u32 = 2;
+
+///anything that can play a seat without a human
+pub trait Agent: Send {
+    ///picks the next action for the current wizard
+    fn choose_action(&mut self, battle: &Battle) -> Result<Action, AgentError>;
+
+    ///called when the action it just picked was illegal, before it gets asked again
+    fn rejected(&mut self, _error: &IllegalAction) {}
+}
+
+#[derive(Debug)]
+pub enum AgentError {
+    Io(io::Error),
+    Timeout,
+    Exited,
+    Protocol(String),
+    Illegal(IllegalAction),
+}
+
+impl Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgentError::Io(e) => write!(f, "couldn't talk to bot: {e}"),
+            AgentError::Timeout => write!(f, "bot took too long to answer"),
+            AgentError::Exited => write!(f, "bot exited"),
+            AgentError::Protocol(e) => write!(f, "bot broke protocol: {e}"),
+            AgentError::Illegal(e) => write!(
+                f,
+                "bot made {MAX_ILLEGAL_ACTIONS} illegal actions in a row, last was {e}"
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for AgentError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Seat {
+    ///the agent playing this seat, humans don't have one
+    pub fn agent(&self) -> Option<Result<Box<dyn Agent>, AgentError>> {
+        match self {
+            Seat::Human | Seat::Remote => None,
+            Seat::Ai => Some(Ok(Box::new(SearchAgent {
+                depth: ai::SEARCH_DEPTH,
+            }))),
+            Seat::Process(command) => Some(
+                ProcessAgent::spawn(command, BOT_TIMEOUT)
+                    .map(|agent| Box::new(agent) as Box<dyn Agent>),
+            ),
+        }
+    }
+}
+
+///an agent picked on the command line: `ai`, `ai:<depth>` or `process:<command>`
+#[derive(Clone)]
+pub enum AgentSpec {
+    Search(usize),
+    Process(String),
+}
+
+impl AgentSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            None if spec == "ai" => Ok(Self::Search(ai::SEARCH_DEPTH)),
+            Some(("ai", depth)) => depth
+                .parse()
+                .map(Self::Search)
+                .map_err(|_| format!("`{depth}` isn't a search depth")),
+            Some(("process", command)) => Ok(Self::Process(command.to_owned())),
+            _ => Err(format!("`{spec}` isn't an agent")),
+        }
+    }
+
+    pub fn start(&self) -> Result<Box<dyn Agent>, String> {
+        match self {
+            AgentSpec::Search(depth) => Ok(Box::new(SearchAgent { depth: *depth })),
+            AgentSpec::Process(command) => ProcessAgent::spawn(command, BOT_TIMEOUT)
+                .map(|agent| Box::new(agent) as Box<dyn Agent>)
+                .map_err(|e| format!("couldn't start `{command}`: {e}")),
+        }
+    }
+
+    pub fn seat(&self) -> Seat {
+        match self {
+            AgentSpec::Search(_) => Seat::Ai,
+            AgentSpec::Process(command) => Seat::Process(command.clone()),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            AgentSpec::Search(depth) => format!("ai:{depth}"),
+            AgentSpec::Process(command) => format!("process:{command}"),
+        }
+    }
+}
+
+///asks `agent` for an action until it picks a legal one
+pub fn next_action(agent: &mut dyn Agent, battle: &Battle) -> Result<Action, AgentError> {
+    let mut last_error = None;
+    for _ in 0..MAX_ILLEGAL_ACTIONS {
+        let action = agent.choose_action(battle)?;
+        match battle.is_legal(action) {
+            Ok(()) => return Ok(action),
+            Err(e) => {
+                agent.rejected(&e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(AgentError::Illegal(
+        last_error.expect("there was at least one attempt"),
+    ))
+}
+
+///plays the battle out with one agent per wizard, a failing agent's turn just ends,
+///returns how many times each agent failed
+pub fn play_out(
+    battle: &mut Battle,
+    agents: &mut [Box<dyn Agent>],
+    max_turns: usize,
+) -> Vec<usize> {
+    let mut failures = vec![0; agents.len()];
+    while !battle.is_over() && battle.turn() < max_turns {
+        let seat = battle.current_player();
+        let action = next_action(agents[seat].as_mut(), battle).unwrap_or_else(|_| {
+            failures[seat] += 1;
+            battle.pass_action()
+        });
+        battle
+            .apply(action)
+            .expect("agent actions are checked by next_action");
+    }
+    failures
+}
+
+///the built in minimax AI
+pub struct SearchAgent {
+    pub depth: usize,
+}
+
+impl Agent for SearchAgent {
+    fn choose_action(&mut self, battle: &Battle) -> Result<Action, AgentError> {
+        Ok(ai::choose_action(battle, self.depth))
+    }
+}
+
+///a bot running as a child process, talking one line at a time over stdin and stdout
+///
+///engine to bot:
+/// - `wizard-fight <version>` once at the start, answered with `ready`
+/// - `state <json>` the whole battle as single line json
+/// - `go <milliseconds>` asks for an action for the current wizard within the time limit,
+///   answered with `action <action>` in the text form of `Action`
+/// - `illegal <reason>` the last action was rejected, a new `go` follows
+/// - `quit` the bot should exit
+///
+///bot lines starting with `info` are ignored so bots can log, anything else is a protocol error
+pub struct ProcessAgent {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    timeout: Duration,
+}
+
+impl ProcessAgent {
+    pub fn spawn(command: &str, timeout: Duration) -> Result<Self, AgentError> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| AgentError::Protocol("no bot command given".to_owned()))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin was piped");
+        let stdout = child.stdout.take().expect("stdout was piped");
+
+        //reading blocks, so it happens on its own thread to allow timeouts
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut agent = Self {
+            child,
+            stdin,
+            lines,
+            timeout,
+        };
+        agent.send(&format!("wizard-fight {PROTOCOL_VERSION}"))?;
+        match agent.receive()?.as_str() {
+            "ready" => Ok(agent),
+            other => Err(AgentError::Protocol(format!(
+                "expected `ready`, got `{other}`"
+            ))),
+        }
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), AgentError> {
+        writeln!(self.stdin, "{line}")?;
+        Ok(self.stdin.flush()?)
+    }
+
+    fn receive(&mut self) -> Result<String, AgentError> {
+        loop {
+            match self.lines.recv_timeout(self.timeout) {
+                Ok(line) if line.starts_with("info") => continue,
+                Ok(line) => return Ok(line.trim().to_owned()),
+                Err(RecvTimeoutError::Timeout) => return Err(AgentError::Timeout),
+                Err(RecvTimeoutError::Disconnected) => return Err(AgentError::Exited),
+            }
+        }
+    }
+}
+
+impl Agent for ProcessAgent {
+    fn choose_action(&mut self, battle: &Battle) -> Result<Action, AgentError> {
+        //the bot only gets to see the tiles its own team picked
+        let seen = battle.seen_by(&[battle.get_current_wizard().team]);
+        let state = serde_json::to_string(&seen)
+            .map_err(|e| AgentError::Protocol(format!("couldn't serialize battle: {e}")))?;
+        //an answer that came in after the last `go` timed out isn't one to this `go`
+        while self.lines.try_recv().is_ok() {}
+        self.send(&format!("state {state}"))?;
+        self.send(&format!("go {}", self.timeout.as_millis()))?;
+        let line = self.receive()?;
+        line.strip_prefix("action ")
+            .ok_or_else(|| AgentError::Protocol(format!("expected an action, got `{line}`")))?
+            .parse()
+            .map_err(AgentError::Protocol)
+    }
+
+    fn rejected(&mut self, error: &IllegalAction) {
+        //if this fails the bot is gone, which the next `choose_action` will notice
+        let _ = self.send(&format!("illegal {}", error.reason));
+    }
+}
+
+impl Drop for ProcessAgent {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::OnceLock;
+
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use super::{
+    action::{Action, SpellTarget},
+    board::{Board, HEIGHT, WIDTH},
+    effects::Effects,
+    map::Placement,
+    position::Position,
+    rng::Rng,
+    spell::Spell,
+    wizard::{Team, Wizard},
+    Battle, SpellSelect,
+};
+
+///how many actions ahead the search looks, a full turn is up to three actions
+pub const SEARCH_DEPTH: usize = 4;
+
+const WIN_SCORE: i64 = 1_000_000;
+
+///picks an action for the current wizard with a depth limited minimax and alpha-beta pruning,
+///ties are broken with the match seed so seeded matches differ but still replay the same
+pub fn choose_action(battle: &Battle, depth: usize) -> Action {
+    let mut rng = Rng::new(battle.setup().seed ^ battle.actions().len() as u64);
+    //nothing has happened yet that would make one starting tile better than another
+    if battle.is_placing() {
+        let tiles = battle.legal_actions();
+        if tiles.is_empty() {
+            return battle.pass_action();
+        }
+        return tiles[rng.below(tiles.len())];
+    }
+    //the search never undoes, so it doesn't need to drag the snapshots around
+    let battle = &Battle {
+        undo_stack: vec![],
+        ..battle.clone()
+    };
+    let team = battle.get_current_wizard().team;
+    let mut alpha = i64::MIN;
+    let mut best_score = i64::MIN;
+    let mut best = vec![Action::EndTurn];
+    for action in candidate_actions(battle) {
+        let Some(next) = simulate(battle, action) else {
+            continue;
+        };
+        //searching ties with an open window keeps their scores exact
+        let score = search(
+            &next,
+            team,
+            depth.saturating_sub(1),
+            alpha.saturating_sub(1),
+            i64::MAX,
+        );
+        if score > best_score {
+            best_score = score;
+            best = vec![action];
+        } else if score == best_score {
+            best.push(action);
+        }
+        alpha = alpha.max(score);
+    }
+    best[rng.below(best.len())]
+}
+
+fn simulate(battle: &Battle, action: Action) -> Option<Battle> {
+    let mut next = battle.clone();
+    next.apply_without_undo(action).ok()?;
+    Some(next)
+}
+
+fn search(battle: &Battle, team: Team, depth: usize, mut alpha: i64, mut beta: i64) -> i64 {
+    if depth == 0 || battle.is_over() {
+        return evaluate(battle, team);
+    }
+    let maximizing = battle.get_current_wizard().team == team;
+    let mut best = if maximizing { i64::MIN } else { i64::MAX };
+    for action in candidate_actions(battle) {
+        let Some(next) = simulate(battle, action) else {
+            continue;
+        };
+        let score = search(&next, team, depth - 1, alpha, beta);
+        if maximizing {
+            best = best.max(score);
+            alpha = alpha.max(score);
+        } else {
+            best = best.min(score);
+            beta = beta.min(score);
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+///legal actions worth looking at, most promising first so pruning kicks in sooner
+fn candidate_actions(battle: &Battle) -> Vec<Action> {
+    let wiz = battle.get_current_wizard();
+    let enemies = battle
+        .wizards()
+        .iter()
+        .filter(|other| other.team != wiz.team && !other.is_dead())
+        .collect_vec();
+    let near_enemy = |pos: super::position::Position| {
+        enemies
+            .iter()
+            .map(|enemy| enemy.position.dist(pos).mag())
+            .min()
+            .unwrap_or(usize::MAX)
+    };
+    battle
+        .legal_actions()
+        .into_iter()
+        //targeting empty ground far from anyone is never better than targeting someone
+        .filter(|action| match action {
+            Action::Cast(_, SpellTarget::Position(pos)) => near_enemy(*pos) <= 1,
+            _ => true,
+        })
+        .sorted_by_key(|action| match action {
+            Action::Cast(..) => 0,
+            Action::Move(pos) | Action::Place(pos) => 1 + near_enemy(*pos),
+            Action::EndTurn => usize::MAX,
+        })
+        .collect()
+}
+
+///how good the battle looks for `team`, positive is good
+pub fn evaluate(battle: &Battle, team: Team) -> i64 {
+    match battle.winner() {
+        Some(winner) if winner == team => return WIN_SCORE,
+        Some(_) => return -WIN_SCORE,
+        None if battle.is_over() => return 0,
+        None => {}
+    }
+    battle
+        .wizards()
+        .iter()
+        .map(|wiz| {
+            let value = wizard_value(wiz) - threat_to(battle, wiz);
+            if wiz.team == team {
+                value
+            } else {
+                -value
+            }
+        })
+        .sum()
+}
+
+fn wizard_value(wiz: &Wizard) -> i64 {
+    if wiz.is_dead() {
+        return -1000;
+    }
+    let effects: i64 = [
+        (Effects::Circulation, 10),
+        (Effects::AuraOfFire, 20),
+        (Effects::StoneSkin, 20),
+        (Effects::Tornado, 20),
+        (Effects::Stagnant, -40),
+    ]
+    .into_iter()
+    .map(|(effect, value)| value * wiz.effect_turns(effect) as i64)
+    .sum();
+    wiz.health as i64 * 4 + wiz.mana as i64 + effects
+}
+
+///rough damage a wizard could take next turn from enemy spells and projectiles
+fn threat_to(battle: &Battle, wiz: &Wizard) -> i64 {
+    if wiz.is_dead() {
+        return 0;
+    }
+    let spells = battle
+        .wizards()
+        .iter()
+        .filter(|enemy| enemy.team != wiz.team && !enemy.is_dead())
+        .map(|enemy| {
+            let dist = enemy.position.dist(wiz.position).mag();
+            enemy
+                .spells
+                .iter()
+                .filter(|spell| enemy.mana >= spell.mana_cost())
+                .filter_map(|spell| spell_threat(*spell))
+                //they can still walk a couple of tiles before casting
+                .filter(|(_, reach)| dist <= reach + 2)
+                .map(|(damage, _)| damage)
+                .max()
+                .unwrap_or(0)
+        })
+        .sum::<i64>();
+    let projectiles = battle
+        .projectiles()
+        .iter()
+        .filter(|proj| proj.owner != wiz.team)
+        .filter(|proj| proj.position.dist(wiz.position).mag() <= proj.speed.max(1))
+        .map(|proj| proj.damage as i64)
+        .sum::<i64>();
+    (spells + projectiles) * 2
+}
+
+///damage and reach of the spells that hurt wizards directly, worked out once from what each one
+///threatens when cast from the middle of an open board
+fn spell_threat(spell: Spell) -> Option<(i64, usize)> {
+    static THREATS: OnceLock<Vec<Option<(i64, usize)>>> = OnceLock::new();
+    THREATS.get_or_init(|| {
+        let mut battle = Battle::new(&SpellSelect {
+            placement: Placement::Fixed,
+            ..Default::default()
+        });
+        let middle = Position::from((WIDTH / 2, HEIGHT / 2));
+        battle.wizards[battle.current_player].position = middle;
+        //out of the way of every ray and flight from the middle
+        let corner = Position::from((0_usize, 0_usize));
+        battle
+            .wizards
+            .iter_mut()
+            .filter(|wiz| wiz.position != middle)
+            .for_each(|wiz| wiz.position = corner);
+        battle.board = Board::new(&battle.wizards, &battle.projectiles);
+        Spell::iter()
+            .map(|spell| {
+                let threat = spell.threat(&battle);
+                let damage = threat.iter().map(|(_, damage)| *damage).max()?;
+                let reach = threat
+                    .iter()
+                    .map(|(tile, _)| middle.dist(*tile).mag())
+                    .max()?;
+                Some((damage as i64, reach))
+            })
+            .collect()
+    })[spell as usize]
+}
@@ -0,0 +1,152 @@
+use std::{process::ExitCode, sync::mpsc::RecvTimeoutError, time::Duration};
+
+use wizard_fight_engine::{
+    agent::{self, AgentSpec},
+    net::{Client, ClientMessage, ServerMessage, DEFAULT_PORT},
+    Battle, SpellChoice,
+};
+
+const USAGE: &str = "\
+usage: wizard-fight-bot [options] [host:port]
+
+joins a hosted match or a wizard-fight-server and plays it with an agent
+
+options:
+  --agent <spec>      `ai`, `ai:<depth>` or `process:<command>` (default ai)
+  --build <w,f,e,i>   water,fire,earth,wind points (default 0,4,0,2)
+  --games <n>         matches to play before quitting (default 1)";
+
+struct Options {
+    address: String,
+    agent: AgentSpec,
+    build: SpellChoice,
+    games: usize,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        address: format!("127.0.0.1:{DEFAULT_PORT}"),
+        agent: AgentSpec::parse("ai")?,
+        build: "0,4,0,2".parse()?,
+        games: 1,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--agent" => options.agent = AgentSpec::parse(&value()?)?,
+            "--build" => options.build = value()?.parse()?,
+            "--games" => {
+                let games = value()?;
+                options.games = games
+                    .parse()
+                    .map_err(|_| format!("`{games}` isn't a number"))?
+            }
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            other if other.starts_with("--") => {
+                return Err(format!("unknown option `{other}`\n\n{USAGE}"))
+            }
+            address => options.address = address.to_owned(),
+        }
+    }
+    Ok(options)
+}
+
+///plays one match, returning once it's over or the host hangs up
+fn play(options: &Options) -> Result<(), String> {
+    let mut agent = options.agent.start()?;
+    let mut client = Client::connect(options.address.as_str(), None)
+        .map_err(|e| format!("couldn't connect to {}: {e}", options.address))?;
+    let mut battle: Option<Battle> = None;
+    //the step we last sent an action at, so we don't send two for the same one
+    let mut sent = None;
+    loop {
+        let message = match client.recv_timeout(Duration::from_secs(1)) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        match message {
+            Some(ServerMessage::Welcome { seat, game, .. }) => {
+                println!("joined game {game} as player {}", seat + 1)
+            }
+            Some(ServerMessage::Room(room)) => {
+                let seat = client.seat().expect("the welcome comes first");
+                if room.players[seat] != options.build {
+                    let build = ClientMessage::Build(seat, options.build.clone());
+                    if client.send(&build).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Some(ServerMessage::State(replay)) => {
+                battle = Some(
+                    replay
+                        .battle_at(replay.actions.len())
+                        .map_err(|e| format!("the host sent an impossible battle: {e}"))?,
+                );
+                sent = None;
+            }
+            Some(ServerMessage::Applied { step, action }) => {
+                let applied = battle
+                    .as_mut()
+                    .filter(|battle| battle.actions().len() == step)
+                    .is_some_and(|battle| battle.apply(action).is_ok());
+                if !applied && client.send(&ClientMessage::Resync).is_err() {
+                    return Ok(());
+                }
+            }
+            Some(ServerMessage::Rejected(reason)) => {
+                eprintln!("rejected: {reason}");
+                sent = None;
+            }
+            Some(
+                ServerMessage::Watching { .. } | ServerMessage::Joined(_) | ServerMessage::Left(_),
+            )
+            | None => {}
+        }
+
+        let Some(battle) = &battle else {
+            continue;
+        };
+        if battle.is_over() {
+            match battle.winner() {
+                Some(team) => println!("{team} won"),
+                None => println!("everyone died"),
+            }
+            return Ok(());
+        }
+        let step = battle.actions().len();
+        if client.seat() == Some(battle.current_player()) && sent != Some(step) {
+            let action = agent::next_action(agent.as_mut(), battle).unwrap_or_else(|e| {
+                eprintln!("{e}, passing");
+                battle.pass_action()
+            });
+            //the server drops a match's players when it calls it a draw
+            if client
+                .send(&ClientMessage::Action { step, action })
+                .is_err()
+            {
+                return Ok(());
+            }
+            sent = Some(step);
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for _ in 0..options.games {
+        if let Err(e) = play(&options) {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
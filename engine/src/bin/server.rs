@@ -0,0 +1,303 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    net::{Ipv4Addr, TcpListener},
+    path::PathBuf,
+    process::ExitCode,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+use serde::Serialize;
+use strum::IntoEnumIterator;
+use wizard_fight_engine::{
+    map::Placement,
+    net::{Handshake, Server, DEFAULT_PORT},
+    wizard::Team,
+    Battle, Seat, SpellChoice, SpellSelect, MAX_PLAYERS, MIN_PLAYERS,
+};
+
+const USAGE: &str = "\
+usage: wizard-fight-server [options]
+
+hosts several matches at once without a window, for the game or for bots to join
+
+options:
+  --port <n>          port to listen on (default 4741)
+  --matches <n>       matches running at the same time (default 4)
+  --players <n>       wizards per match, 2 to 8 (default 2)
+  --teams <n>         teams the players are split into in seat order, 0 for free-for-all
+                      (default 0)
+  --placement <mode>  how wizards get their starting tiles: fixed, chosen, random or mirrored
+                      (default chosen)
+  --turn-time <secs>  ends the turn of anyone slower than this, 0 for no limit (default 60)
+  --max-turns <n>     turns before a match is called a draw (default 200)
+  --spectator-delay <turns>
+                      how far behind the players spectators are (default 0)
+  --replays <dir>     where each finished match's replay goes (default replays)
+  --results <path>    appends one json line per finished match";
+
+struct Options {
+    port: u16,
+    matches: usize,
+    players: usize,
+    teams: usize,
+    placement: Placement,
+    turn_time: u64,
+    max_turns: usize,
+    spectator_delay: usize,
+    replays: PathBuf,
+    results: Option<PathBuf>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        port: DEFAULT_PORT,
+        matches: 4,
+        players: 2,
+        teams: 0,
+        placement: Placement::default(),
+        turn_time: 60,
+        max_turns: 200,
+        spectator_delay: 0,
+        replays: PathBuf::from("replays"),
+        results: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        let number = |value: String| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("`{value}` isn't a number"))
+        };
+        match arg.as_str() {
+            "--port" => {
+                let port = value()?;
+                options.port = port.parse().map_err(|_| format!("`{port}` isn't a port"))?
+            }
+            "--matches" => options.matches = number(value()?)?.max(1),
+            "--players" => options.players = number(value()?)?,
+            "--teams" => options.teams = number(value()?)?,
+            "--placement" => {
+                let placement = value()?;
+                options.placement = Placement::iter()
+                    .find(|mode| mode.to_string().eq_ignore_ascii_case(&placement))
+                    .ok_or_else(|| format!("`{placement}` isn't a placement mode"))?
+            }
+            "--turn-time" => options.turn_time = number(value()?)? as u64,
+            "--max-turns" => options.max_turns = number(value()?)?,
+            "--spectator-delay" => options.spectator_delay = number(value()?)?,
+            "--replays" => options.replays = value()?.into(),
+            "--results" => options.results = Some(value()?.into()),
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            other => return Err(format!("unknown option `{other}`\n\n{USAGE}")),
+        }
+    }
+    if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&options.players) {
+        return Err(format!(
+            "a match needs between {MIN_PLAYERS} and {MAX_PLAYERS} players"
+        ));
+    }
+    if options.teams == 1 || options.teams > options.players {
+        return Err(format!(
+            "{} players can't be split into {} teams",
+            options.players, options.teams
+        ));
+    }
+    Ok(options)
+}
+
+#[derive(Serialize)]
+struct MatchResult {
+    game: u64,
+    seed: u64,
+    winner: Option<Team>,
+    turns: usize,
+    replay: PathBuf,
+}
+
+///the matches being played, a finished one makes room for a new one
+struct Matches {
+    servers: Vec<Server>,
+    ///the steps each match was at when it was last looked at
+    checked: Vec<usize>,
+    next_game: u64,
+    options: Options,
+}
+
+impl Matches {
+    fn new_match(&mut self) -> Server {
+        let server = Server::room(
+            SpellSelect {
+                players: vec![SpellChoice::default(); self.options.players],
+                seats: vec![Seat::Remote; self.options.players],
+                //the first wizard of every team sits first, so they take turns in seat order
+                teams: match self.options.teams {
+                    0 => Team::iter().take(self.options.players).collect(),
+                    teams => Team::iter()
+                        .take(teams)
+                        .cycle()
+                        .take(self.options.players)
+                        .collect(),
+                },
+                placement: self.options.placement,
+            },
+            self.next_game,
+        );
+        if self.options.turn_time > 0 {
+            server.set_turn_time(Duration::from_secs(self.options.turn_time));
+        }
+        server.set_spectator_delay(self.options.spectator_delay);
+        self.next_game += 1;
+        server
+    }
+
+    ///a reconnecting client gets its old match back, anyone else the first one with room,
+    ///spectators the first one that started
+    fn seat(&self, handshake: Handshake) {
+        let server = handshake
+            .game
+            .and_then(|game| self.servers.iter().find(|server| server.game() == game))
+            .or_else(|| {
+                self.servers.iter().find(|server| {
+                    if handshake.spectate {
+                        server.steps().is_some()
+                    } else {
+                        server.steps().is_none() && server.free_seats() > 0
+                    }
+                })
+            })
+            .or_else(|| self.servers.first().filter(|_| handshake.spectate));
+        match server {
+            Some(server) => server.adopt(handshake),
+            None => handshake.reject("every match is full, try again later"),
+        }
+    }
+
+    fn tick(&mut self) {
+        for i in 0..self.servers.len() {
+            let server = &self.servers[i];
+            let Some(steps) = server.steps() else {
+                if server.start().is_ok() {
+                    println!("game {}: started", server.game());
+                }
+                continue;
+            };
+            //only matches that moved on since get copied to check on
+            if steps == self.checked[i] {
+                continue;
+            }
+            self.checked[i] = steps;
+            let Some(battle) = server.battle() else {
+                continue;
+            };
+            if battle.is_over() || battle.turn() >= self.options.max_turns {
+                let game = server.game();
+                self.finish(game, &battle);
+                //dropping the old server disconnects its players
+                self.servers[i] = self.new_match();
+                self.checked[i] = 0;
+            }
+        }
+    }
+
+    fn finish(&self, game: u64, battle: &Battle) {
+        let replay = self
+            .options
+            .replays
+            .join(format!("game-{game}.replay.json"));
+        if let Err(e) = battle.replay().save(&replay) {
+            eprintln!("game {game}: couldn't save replay: {e}");
+        }
+        let result = MatchResult {
+            game,
+            seed: battle.setup().seed,
+            winner: battle.winner(),
+            turns: battle.turn(),
+            replay,
+        };
+        match result.winner {
+            Some(team) => println!("game {game}: {team} wins on turn {}", result.turns + 1),
+            None => println!("game {game}: draw after {} turns", result.turns),
+        }
+        if let Some(path) = &self.options.results {
+            let line = serde_json::to_string(&result).expect("results are plain data");
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{line}"));
+            if let Err(e) = written {
+                eprintln!("couldn't write results to {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = fs::create_dir_all(&options.replays) {
+        eprintln!("couldn't create {}: {e}", options.replays.display());
+        return ExitCode::FAILURE;
+    }
+    let listener = match TcpListener::bind((Ipv4Addr::UNSPECIFIED, options.port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("couldn't listen on port {}: {e}", options.port);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!(
+        "listening on port {} with {} matches of {} players",
+        options.port, options.matches, options.players
+    );
+
+    let mut matches = Matches {
+        servers: vec![],
+        checked: vec![],
+        next_game: 0,
+        options,
+    };
+    matches.servers = (0..matches.options.matches)
+        .map(|_| matches.new_match())
+        .collect();
+    matches.checked = vec![0; matches.servers.len()];
+    let matches = Arc::new(Mutex::new(matches));
+
+    {
+        let matches = matches.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let matches = matches.clone();
+                //a slow hello shouldn't hold up everyone else
+                thread::spawn(move || {
+                    if let Some(handshake) = Handshake::read(stream) {
+                        matches
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .seat(handshake);
+                    }
+                });
+            }
+        });
+    }
+
+    loop {
+        thread::sleep(Duration::from_millis(250));
+        matches
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .tick();
+    }
+}
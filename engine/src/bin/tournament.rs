@@ -0,0 +1,358 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use itertools::Itertools;
+use serde::Serialize;
+use wizard_fight_engine::{
+    agent::{self, Agent, AgentSpec},
+    log::LogEvent,
+    map::{Map, Placement},
+    wizard::Team,
+    Battle, MatchSetup, SpellChoice,
+};
+
+const USAGE: &str = "\
+usage: wizard-fight-tournament [options]
+
+runs seeded matches between two agents without a window and writes a report
+
+options:
+  --games <n>         games per pairing (default 100)
+  --seed <n>          seed of the first game, the rest count up from it (default 0)
+  --threads <n>       worker threads (default: every core)
+  --max-turns <n>     turns before a game is called a draw (default 200)
+  --agent <spec>      give twice, once per seat: `ai`, `ai:<depth>` or `process:<command>`
+                      (default ai:2 for both)
+  --build <w,f,e,i>   give twice, water,fire,earth,wind points per seat (default 0,4,0,2)
+  --all-builds        play every legal build against every other one and rank them
+  --format <json|csv> report format (default json)
+  --out <path>        where to write the report (default stdout)";
+
+struct Options {
+    games: usize,
+    seed: u64,
+    threads: usize,
+    max_turns: usize,
+    agents: [AgentSpec; 2],
+    builds: [SpellChoice; 2],
+    all_builds: bool,
+    csv: bool,
+    out: Option<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        games: 100,
+        seed: 0,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        max_turns: 200,
+        agents: [AgentSpec::Search(2), AgentSpec::Search(2)],
+        builds: ["0,4,0,2".parse()?, "0,4,0,2".parse()?],
+        all_builds: false,
+        csv: false,
+        out: None,
+    };
+    let mut agents = vec![];
+    let mut builds: Vec<SpellChoice> = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        let number = |value: String| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("`{value}` isn't a number"))
+        };
+        match arg.as_str() {
+            "--games" => options.games = number(value()?)?,
+            "--seed" => options.seed = number(value()?)? as u64,
+            "--threads" => options.threads = number(value()?)?.max(1),
+            "--max-turns" => options.max_turns = number(value()?)?,
+            "--agent" => agents.push(AgentSpec::parse(&value()?)?),
+            "--build" => builds.push(value()?.parse()?),
+            "--all-builds" => options.all_builds = true,
+            "--format" => match value()?.as_str() {
+                "json" => options.csv = false,
+                "csv" => options.csv = true,
+                other => return Err(format!("`{other}` isn't a format")),
+            },
+            "--out" => options.out = Some(value()?),
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            other => return Err(format!("unknown option `{other}`\n\n{USAGE}")),
+        }
+    }
+    match agents.len() {
+        0 => {}
+        1 => options.agents = [agents[0].clone(), agents[0].clone()],
+        2 => options.agents = [agents[0].clone(), agents[1].clone()],
+        _ => return Err("at most two agents can be given".to_owned()),
+    }
+    match builds.len() {
+        0 => {}
+        2 => options.builds = [builds[0].clone(), builds[1].clone()],
+        _ => return Err("give either no builds or one per seat".to_owned()),
+    }
+    Ok(options)
+}
+
+struct Game {
+    seed: u64,
+    ///build and agent for each seat, seat 0 goes first
+    builds: [SpellChoice; 2],
+    agents: [AgentSpec; 2],
+}
+
+#[derive(Serialize)]
+struct MatchRecord {
+    seed: u64,
+    builds: [SpellChoice; 2],
+    agents: [String; 2],
+    ///seat of the winner, none for draws
+    winner: Option<usize>,
+    turns: usize,
+    ///damage each seat dealt to the other, by what dealt it
+    damage: [BTreeMap<String, usize>; 2],
+    mana_spent: [usize; 2],
+    agent_failures: [usize; 2],
+}
+
+fn play(game: &Game, max_turns: usize) -> Result<MatchRecord, String> {
+    let mut agents: Vec<Box<dyn Agent>> = game.agents.iter().map(|a| a.start()).try_collect()?;
+    let mut battle = Battle::from_setup(MatchSetup {
+        players: game.builds.to_vec(),
+        seats: game.agents.iter().map(AgentSpec::seat).collect(),
+        teams: vec![Team::Red, Team::Blue],
+        map: Map::default(),
+        placement: Placement::Random,
+        seed: game.seed,
+    });
+    let failures = agent::play_out(&mut battle, &mut agents, max_turns);
+
+    let mut damage: [BTreeMap<String, usize>; 2] = Default::default();
+    let mut mana_spent = [0; 2];
+    for entry in battle.log() {
+        match &entry.event {
+            LogEvent::WizardDamaged {
+                wizard,
+                amount,
+                source,
+                ..
+            } => {
+                //in a duel every bit of damage comes from the other seat
+                *damage[1 - wizard].entry(source.to_string()).or_default() += amount;
+            }
+            LogEvent::SpellCast { wizard, spell } => mana_spent[*wizard] += spell.mana_cost(),
+            _ => {}
+        }
+    }
+    let winner = battle.winner().and_then(|team| {
+        battle
+            .wizards()
+            .iter()
+            .position(|wizard| wizard.team == team)
+    });
+    Ok(MatchRecord {
+        seed: game.seed,
+        builds: game.builds.clone(),
+        agents: game.agents.clone().map(|a| a.name()),
+        winner,
+        turns: battle.turn(),
+        damage,
+        mana_spent,
+        agent_failures: [failures[0], failures[1]],
+    })
+}
+
+///runs every game on `threads` workers, in whatever order they finish
+fn run(games: &[Game], threads: usize, max_turns: usize) -> Result<Vec<MatchRecord>, String> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(games.len()));
+    thread::scope(|scope| {
+        for _ in 0..threads.min(games.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(game) = games.get(i) else {
+                    break;
+                };
+                let record = play(game, max_turns);
+                let failed = record.is_err();
+                results.lock().expect("workers don't panic").push(record);
+                if failed {
+                    //skip the rest, the same agent would fail again
+                    next.store(games.len(), Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .expect("workers don't panic")
+        .into_iter()
+        .collect()
+}
+
+#[derive(Serialize)]
+struct BuildSummary {
+    build: SpellChoice,
+    games: usize,
+    wins: usize,
+    losses: usize,
+    draws: usize,
+    win_rate: f64,
+    average_turns: f64,
+    damage: BTreeMap<String, usize>,
+    mana_spent: usize,
+}
+
+fn summarize(records: &[MatchRecord]) -> Vec<BuildSummary> {
+    let mut summaries: Vec<BuildSummary> = vec![];
+    for record in records {
+        for seat in 0..2 {
+            let build = &record.builds[seat];
+            let i = match summaries.iter().position(|s| s.build == *build) {
+                Some(i) => i,
+                None => {
+                    summaries.push(BuildSummary {
+                        build: build.clone(),
+                        games: 0,
+                        wins: 0,
+                        losses: 0,
+                        draws: 0,
+                        win_rate: 0.0,
+                        average_turns: 0.0,
+                        damage: BTreeMap::new(),
+                        mana_spent: 0,
+                    });
+                    summaries.len() - 1
+                }
+            };
+            let summary = &mut summaries[i];
+            summary.games += 1;
+            match record.winner {
+                Some(winner) if winner == seat => summary.wins += 1,
+                Some(_) => summary.losses += 1,
+                None => summary.draws += 1,
+            }
+            summary.average_turns += record.turns as f64;
+            for (source, amount) in &record.damage[seat] {
+                *summary.damage.entry(source.clone()).or_default() += amount;
+            }
+            summary.mana_spent += record.mana_spent[seat];
+        }
+    }
+    summaries.iter_mut().for_each(|summary| {
+        //draws count as half a win so stalemating builds rank in the middle
+        summary.win_rate =
+            (summary.wins as f64 + summary.draws as f64 / 2.0) / summary.games as f64;
+        summary.average_turns /= summary.games as f64;
+    });
+    summaries.sort_by(|a, b| b.win_rate.total_cmp(&a.win_rate));
+    summaries
+}
+
+#[derive(Serialize)]
+struct Report {
+    builds: Vec<BuildSummary>,
+    matches: Vec<MatchRecord>,
+}
+
+fn to_csv(report: &Report) -> String {
+    let mut csv =
+        "rank,water,fire,earth,wind,games,wins,losses,draws,win_rate,average_turns,mana_spent,damage\n"
+            .to_owned();
+    for (rank, summary) in report.builds.iter().enumerate() {
+        let build = &summary.build;
+        csv += &format!(
+            "{},{},{},{},{},{},{},{},{},{:.4},{:.2},{},\"{}\"\n",
+            rank + 1,
+            build.water,
+            build.fire,
+            build.earth,
+            build.wind,
+            summary.games,
+            summary.wins,
+            summary.losses,
+            summary.draws,
+            summary.win_rate,
+            summary.average_turns,
+            summary.mana_spent,
+            summary
+                .damage
+                .iter()
+                .map(|(source, amount)| format!("{source}={amount}"))
+                .join(";")
+        );
+    }
+    csv
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let pairings = if options.all_builds {
+        SpellChoice::all_builds()
+            .into_iter()
+            .permutations(2)
+            .map(|builds| [builds[0].clone(), builds[1].clone()])
+            .collect_vec()
+    } else {
+        vec![options.builds.clone()]
+    };
+    let games = pairings
+        .iter()
+        .cartesian_product(0..options.games)
+        .enumerate()
+        .map(|(i, (builds, _))| Game {
+            seed: options.seed + i as u64,
+            builds: builds.clone(),
+            agents: options.agents.clone(),
+        })
+        .collect_vec();
+    eprintln!(
+        "playing {} games on {} threads",
+        games.len(),
+        options.threads
+    );
+
+    let mut matches = match run(&games, options.threads, options.max_turns) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    matches.sort_by_key(|record| record.seed);
+    let report = Report {
+        builds: summarize(&matches),
+        matches,
+    };
+
+    let output = if options.csv {
+        to_csv(&report)
+    } else {
+        serde_json::to_string_pretty(&report).expect("reports are plain data")
+    };
+    match &options.out {
+        Some(path) => {
+            if let Err(e) = fs::write(path, output) {
+                eprintln!("couldn't write {path}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => println!("{output}"),
+    }
+    ExitCode::SUCCESS
+}
@@ -1,10 +1,11 @@
-use std::{array::from_fn, mem::swap};
+use std::array::from_fn;
 
 use super::{position::Position, projectile::Projectile, wizard::Wizard};
 
 pub const WIDTH: usize = 30;
 pub const HEIGHT: usize = 20;
 
+#[derive(Clone)]
 pub struct Board {
     board: [Option<Entity>; WIDTH * HEIGHT],
 }
@@ -25,6 +26,10 @@ impl Board {
         temp
     }
 
+    pub fn is_in_bounds(pos: Position) -> bool {
+        pos.x < WIDTH && pos.y < HEIGHT
+    }
+
     pub fn empty() -> Self {
         Self {
             board: from_fn(|_| None),
@@ -45,6 +50,12 @@ impl Board {
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 #[derive(Clone, Copy, strum_macros::EnumIs)]
 pub enum Entity {
     Wizard(usize),
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+use super::{action::SpellTarget, log::LogEvent, spell::Spell, Battle};
+
+#[derive(
+    strum_macros::EnumCount,
+    strum_macros::FromRepr,
+    strum_macros::Display,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum Effects {
+    Circulation = 0,
+    Stagnant,
+    AuraOfFire,
+    StoneSkin,
+    Tornado,
+}
+
+///what happens when a wizard gets an effect they already have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    ///the new one replaces the old one
+    Refresh,
+    ///the turns left add up
+    Stack,
+}
+
+///an effect on a wizard, who put it there and how many turns it has left
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Effect {
+    pub kind: Effects,
+    pub source: usize,
+    pub turns: usize,
+}
+
+impl Effects {
+    pub fn stacking(self) -> Stacking {
+        match self {
+            Effects::Stagnant => Stacking::Stack,
+            Effects::Circulation | Effects::AuraOfFire | Effects::StoneSkin | Effects::Tornado => {
+                Stacking::Refresh
+            }
+        }
+    }
+
+    ///whether the effect wears off over the turns the wizard sits out instead of the ones they take
+    pub fn ticks_on_skipped_turns(self) -> bool {
+        self == Effects::Stagnant
+    }
+
+    pub fn modify_incoming_damage(self, damage: usize) -> usize {
+        match self {
+            Effects::StoneSkin => damage.saturating_sub(20),
+            _ => damage,
+        }
+    }
+
+    pub fn modify_movement(self, distance: usize) -> usize {
+        match self {
+            Effects::Circulation | Effects::Tornado => distance * 2,
+            Effects::AuraOfFire => distance / 2,
+            _ => distance,
+        }
+    }
+
+    pub fn modify_mana_gain(self, mana: usize) -> usize {
+        match self {
+            Effects::Tornado => mana / 2,
+            _ => mana,
+        }
+    }
+}
+
+impl Battle {
+    ///gives the wizard an effect from `source`, combined with one they already have by its
+    ///stacking rule
+    pub fn apply_effect(&mut self, wizard: usize, kind: Effects, source: usize, turns: usize) {
+        if turns == 0 {
+            return;
+        }
+        let effect = Effect {
+            kind,
+            source,
+            turns,
+        };
+        let effects = &mut self.wizards[wizard].effects;
+        let total = match effects.iter_mut().find(|e| e.kind == kind) {
+            None => {
+                effects.push(effect);
+                turns
+            }
+            Some(old) => match kind.stacking() {
+                Stacking::Refresh => {
+                    *old = effect;
+                    turns
+                }
+                Stacking::Stack => {
+                    old.source = source;
+                    old.turns += turns;
+                    old.turns
+                }
+            },
+        };
+        self.log_event(LogEvent::EffectApplied {
+            wizard,
+            effect: kind,
+            by: source,
+            turns: total,
+        });
+    }
+
+    ///ticks down the wizard's effects for a turn they took, or sat out if `skipped`
+    pub(super) fn tick_effects(&mut self, wizard: usize, skipped: bool) {
+        let effects = &mut self.wizards[wizard].effects;
+        let mut expired = vec![];
+        effects.retain_mut(|effect| {
+            if effect.kind.ticks_on_skipped_turns() != skipped {
+                return true;
+            }
+            effect.turns = effect.turns.saturating_sub(1);
+            if effect.turns == 0 {
+                expired.push(*effect);
+            }
+            effect.turns > 0
+        });
+        for effect in expired {
+            self.log_event(LogEvent::EffectExpired {
+                wizard,
+                effect: effect.kind,
+            });
+        }
+    }
+
+    fn on_turn_end(&mut self, _wizard: usize, effect: Effect) {
+        let spell = match effect.kind {
+            Effects::AuraOfFire => Spell::AuraOfFire,
+            Effects::Tornado => Spell::Tornado,
+            Effects::Circulation | Effects::Stagnant | Effects::StoneSkin => return,
+        };
+        let area = spell.area(self, SpellTarget::None);
+        self.blast(&area, spell.damage(), spell);
+    }
+
+    ///runs the hooks of every effect the wizard has as their turn ends
+    pub(super) fn end_effects(&mut self, wizard: usize) {
+        for effect in self.wizards[wizard].effects.clone() {
+            self.on_turn_end(wizard, effect);
+        }
+    }
+}
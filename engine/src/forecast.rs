@@ -0,0 +1,125 @@
+use itertools::Itertools;
+
+use super::{
+    action::{Action, IllegalAction},
+    effects::Effect,
+    log::LogEvent,
+    position::Position,
+    projectile::ProjectileType,
+    Battle,
+};
+
+///how a wizard would come out of an action
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WizardForecast {
+    pub wizard: usize,
+    ///where the wizard stands before the action
+    pub position: Position,
+    ///before and after
+    pub health: (usize, usize),
+    pub mana: (usize, usize),
+    ///mana taken by mana drain, which also shows up in `mana`
+    pub drained: usize,
+    ///the effects the action puts on them, with the turns they'd have
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectileForecast {
+    pub position: Position,
+    pub projectile_type: ProjectileType,
+    ///the damage it carries before and after, none if it gets destroyed
+    pub damage: (usize, Option<usize>),
+}
+
+///what an action would do, worked out by taking it on a copy of the battle
+#[derive(Clone, Debug, Default)]
+pub struct Forecast {
+    ///only the wizards the action changes
+    pub wizards: Vec<WizardForecast>,
+    ///only the projectiles that get hurt or destroyed
+    pub projectiles: Vec<ProjectileForecast>,
+    ///everything the action would log
+    pub events: Vec<LogEvent>,
+}
+
+impl Battle {
+    pub fn forecast(&self, action: Action) -> Result<Forecast, IllegalAction> {
+        let mut after = self.clone();
+        let start = after.log.len();
+        after.apply(action)?;
+        let events = after.log[start..]
+            .iter()
+            .map(|entry| entry.event.clone())
+            .collect_vec();
+
+        let wizards = self
+            .wizards
+            .iter()
+            .zip(&after.wizards)
+            .enumerate()
+            .filter_map(|(wizard, (before, after))| {
+                let effects = events
+                    .iter()
+                    .filter_map(|event| match *event {
+                        LogEvent::EffectApplied {
+                            wizard: w,
+                            effect,
+                            by,
+                            turns,
+                        } if w == wizard => Some(Effect {
+                            kind: effect,
+                            source: by,
+                            turns,
+                        }),
+                        _ => None,
+                    })
+                    .collect_vec();
+                let drained = events
+                    .iter()
+                    .filter_map(|event| match *event {
+                        LogEvent::ManaDrained {
+                            wizard: w, mana, ..
+                        } if w == wizard => Some(mana),
+                        _ => None,
+                    })
+                    .sum();
+                (before.health != after.health || before.mana != after.mana || !effects.is_empty())
+                    .then_some(WizardForecast {
+                        wizard,
+                        position: before.position,
+                        health: (before.health, after.health),
+                        mana: (before.mana, after.mana),
+                        drained,
+                        effects,
+                    })
+            })
+            .collect();
+
+        //spells don't move projectiles, so a projectile is the one on the same tile afterwards
+        let projectiles = self
+            .projectiles
+            .iter()
+            .filter_map(|before| {
+                let damage = after
+                    .projectiles
+                    .iter()
+                    .find(|p| {
+                        p.position == before.position && p.projectile_type == before.projectile_type
+                    })
+                    .map(|p| p.damage);
+                (damage != Some(before.damage)).then_some(ProjectileForecast {
+                    position: before.position,
+                    projectile_type: before.projectile_type,
+                    damage: (before.damage, damage),
+                })
+            })
+            .collect();
+
+        Ok(Forecast {
+            wizards,
+            projectiles,
+            events,
+        })
+    }
+}
@@ -0,0 +1,318 @@
+use std::{fmt::Display, thread};
+
+use strum::{EnumCount, IntoEnumIterator};
+
+use super::{
+    action::{Action, IllegalAction, SpellTarget},
+    board::{HEIGHT, WIDTH},
+    effects::Effects,
+    map::{Map, Placement},
+    position::{Direction, Position},
+    projectile::ProjectileType,
+    spell::Spell,
+    wizard::Team,
+    Battle, MatchSetup, Seat, SpellChoice, MAX_MANA,
+};
+
+pub const TILES: usize = WIDTH * HEIGHT;
+
+//board channels, each WIDTH * HEIGHT floats, seen from the wizard whose turn it is
+pub const OWN_WIZARD: usize = 0;
+pub const ENEMY_WIZARD: usize = 1;
+///health / 100 on every wizard's tile
+pub const HEALTH: usize = 2;
+///mana / MAX_MANA on every wizard's tile
+pub const MANA: usize = 3;
+///turns left of each effect on the affected wizard's tile, one channel per `Effects`
+pub const EFFECTS: usize = 4;
+///one channel per `ProjectileType`, 1 where a projectile of that type is
+pub const PROJECTILES: usize = EFFECTS + Effects::COUNT;
+pub const OWN_PROJECTILE: usize = PROJECTILES + ProjectileType::COUNT;
+pub const PROJECTILE_DAMAGE: usize = OWN_PROJECTILE + 1;
+pub const PROJECTILE_LIFETIME: usize = PROJECTILE_DAMAGE + 1;
+///tiles nothing can walk into
+pub const TERRAIN: usize = PROJECTILE_LIFETIME + 1;
+pub const CHANNELS: usize = TERRAIN + 1;
+
+///every target a spell can be given: none, a direction or a tile
+const SPELL_TARGETS: usize = 1 + Direction::COUNT + TILES;
+const MOVE_ACTIONS: usize = 1;
+const CAST_ACTIONS: usize = MOVE_ACTIONS + TILES;
+///end turn, a move to every tile, and every spell with every target
+pub const ACTION_COUNT: usize = CAST_ACTIONS + Spell::COUNT * SPELL_TARGETS;
+
+pub const DEFAULT_MAX_TURNS: usize = 200;
+
+pub struct Observation {
+    ///`CHANNELS` planes of `HEIGHT` rows of `WIDTH` tiles
+    pub board: Vec<f32>,
+    ///which action indices are legal right now
+    pub action_mask: Vec<bool>,
+    ///the wizard who acts next
+    pub player: usize,
+}
+
+#[derive(Debug)]
+pub enum StepError {
+    OutOfRange(usize),
+    ///a cast of a spell the current wizard doesn't know
+    UnknownSpell(usize),
+    Illegal(IllegalAction),
+    ///the episode is done, call `reset`
+    Done,
+}
+
+impl Display for StepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::OutOfRange(i) => write!(f, "action {i} is out of range"),
+            StepError::UnknownSpell(i) => write!(f, "action {i} casts an unknown spell"),
+            StepError::Illegal(illegal) => write!(f, "{illegal}"),
+            StepError::Done => write!(f, "the episode is over"),
+        }
+    }
+}
+
+pub fn action_index(battle: &Battle, action: Action) -> usize {
+    match action {
+        Action::EndTurn => 0,
+        //placing and moving never happen in the same turn, so they share the tile indices
+        Action::Move(pos) | Action::Place(pos) => MOVE_ACTIONS + tile_index(pos),
+        Action::Cast(i, target) => {
+            let spell = battle.get_current_wizard().spells[i];
+            let target = match target {
+                SpellTarget::None => 0,
+                SpellTarget::Direction(dir) => 1 + dir as usize,
+                SpellTarget::Position(pos) => 1 + Direction::COUNT + tile_index(pos),
+            };
+            CAST_ACTIONS + spell as usize * SPELL_TARGETS + target
+        }
+    }
+}
+
+///the action an index stands for, casts of spells the current wizard doesn't know are `None`
+pub fn index_action(battle: &Battle, index: usize) -> Option<Action> {
+    match index {
+        0 => Some(Action::EndTurn),
+        i if i < CAST_ACTIONS && battle.is_placing() => {
+            Some(Action::Place(tile_position(i - MOVE_ACTIONS)))
+        }
+        i if i < CAST_ACTIONS => Some(Action::Move(tile_position(i - MOVE_ACTIONS))),
+        i if i < ACTION_COUNT => {
+            let spell = Spell::from_repr((i - CAST_ACTIONS) / SPELL_TARGETS)?;
+            let spell_index = battle
+                .get_current_wizard()
+                .spells
+                .iter()
+                .position(|s| *s == spell)?;
+            let target = match (i - CAST_ACTIONS) % SPELL_TARGETS {
+                0 => SpellTarget::None,
+                t if t <= Direction::COUNT => SpellTarget::Direction(Direction::from_repr(t - 1)?),
+                t => SpellTarget::Position(tile_position(t - 1 - Direction::COUNT)),
+            };
+            Some(Action::Cast(spell_index, target))
+        }
+        _ => None,
+    }
+}
+
+fn tile_index(pos: Position) -> usize {
+    pos.y * WIDTH + pos.x
+}
+
+fn tile_position(i: usize) -> Position {
+    (i % WIDTH, i / WIDTH).into()
+}
+
+impl Observation {
+    pub fn new(battle: &Battle) -> Self {
+        let mut board = vec![0.0; CHANNELS * TILES];
+        let mut set = |channel: usize, pos: Position, value: f32| {
+            board[channel * TILES + tile_index(pos)] = value;
+        };
+        let team = battle.get_current_wizard().team;
+        for wiz in battle.wizards().iter().filter(|wiz| !wiz.is_dead()) {
+            let side = if wiz.team == team {
+                OWN_WIZARD
+            } else {
+                ENEMY_WIZARD
+            };
+            set(side, wiz.position, 1.0);
+            set(HEALTH, wiz.position, wiz.health as f32 / 100.0);
+            set(MANA, wiz.position, wiz.mana as f32 / MAX_MANA as f32);
+            for effect in &wiz.effects {
+                set(
+                    EFFECTS + effect.kind as usize,
+                    wiz.position,
+                    effect.turns as f32,
+                );
+            }
+        }
+        for proj in battle.projectiles() {
+            set(
+                PROJECTILES + proj.projectile_type as usize,
+                proj.position,
+                1.0,
+            );
+            if proj.owner == team {
+                set(OWN_PROJECTILE, proj.position, 1.0);
+            }
+            set(PROJECTILE_DAMAGE, proj.position, proj.damage as f32 / 100.0);
+            set(PROJECTILE_LIFETIME, proj.position, proj.lifetime as f32);
+            if !proj.passable {
+                set(TERRAIN, proj.position, 1.0);
+            }
+        }
+
+        let mut action_mask = vec![false; ACTION_COUNT];
+        for action in battle.legal_actions() {
+            action_mask[action_index(battle, action)] = true;
+        }
+        Self {
+            board,
+            action_mask,
+            player: battle.current_player(),
+        }
+    }
+}
+
+///one battle played by an outside learner, every wizard's actions go through `step`
+pub struct Env {
+    battle: Battle,
+    ///the battle is called a draw after this many turns
+    pub max_turns: usize,
+}
+
+impl Env {
+    pub fn new(seed: u64, builds: Vec<SpellChoice>) -> Result<Self, String> {
+        Ok(Self {
+            battle: Self::new_battle(seed, builds)?,
+            max_turns: DEFAULT_MAX_TURNS,
+        })
+    }
+
+    ///builds have to spend every point like the ones `SpellChoice::all_builds` lists
+    fn new_battle(seed: u64, builds: Vec<SpellChoice>) -> Result<Battle, String> {
+        if let Some(i) = builds.iter().position(|build| !build.is_complete()) {
+            return Err(format!("build {i} doesn't spend every point legally"));
+        }
+        Ok(Battle::from_setup(MatchSetup {
+            seats: vec![Seat::Human; builds.len()],
+            teams: Team::iter().take(builds.len()).collect(),
+            players: builds,
+            map: Map::default(),
+            placement: Placement::Random,
+            seed,
+        }))
+    }
+
+    pub fn reset(&mut self, seed: u64, builds: Vec<SpellChoice>) -> Result<Observation, String> {
+        self.battle = Self::new_battle(seed, builds)?;
+        Ok(self.observation())
+    }
+
+    pub fn battle(&self) -> &Battle {
+        &self.battle
+    }
+
+    pub fn observation(&self) -> Observation {
+        Observation::new(&self.battle)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.battle.is_over() || self.battle.turn() >= self.max_turns
+    }
+
+    ///the reward goes to the wizard who took the action: 1 for winning, -1 for dying, 0 otherwise
+    pub fn step(&mut self, action_index: usize) -> Result<(Observation, f32, bool), StepError> {
+        if self.is_done() {
+            return Err(StepError::Done);
+        }
+        if action_index >= ACTION_COUNT {
+            return Err(StepError::OutOfRange(action_index));
+        }
+        let action = index_action(&self.battle, action_index)
+            .ok_or(StepError::UnknownSpell(action_index))?;
+        let player = self.battle.current_player();
+        self.battle.apply(action).map_err(StepError::Illegal)?;
+        //nobody needs to take back a learner's moves
+        self.battle.undo_stack.clear();
+
+        let wiz = self.battle.get_wizard(player);
+        let reward = match self.battle.winner() {
+            Some(team) if team == wiz.team => 1.0,
+            Some(_) => -1.0,
+            None if wiz.is_dead() => -1.0,
+            None => 0.0,
+        };
+        Ok((self.observation(), reward, self.is_done()))
+    }
+}
+
+///many environments stepped together on every core, each one starts over as soon as it's done
+pub struct VecEnv {
+    pub envs: Vec<Env>,
+    builds: Vec<SpellChoice>,
+    next_seed: u64,
+}
+
+impl VecEnv {
+    pub fn new(count: usize, seed: u64, builds: Vec<SpellChoice>) -> Result<Self, String> {
+        Ok(Self {
+            envs: (0..count)
+                .map(|i| Env::new(seed + i as u64, builds.clone()))
+                .collect::<Result<_, _>>()?,
+            builds,
+            next_seed: seed + count as u64,
+        })
+    }
+
+    pub fn observations(&self) -> Vec<Observation> {
+        self.envs.iter().map(Env::observation).collect()
+    }
+
+    ///one action per environment, finished ones return the first observation of the next episode
+    pub fn step(
+        &mut self,
+        action_indices: &[usize],
+    ) -> Vec<Result<(Observation, f32, bool), StepError>> {
+        assert_eq!(action_indices.len(), self.envs.len());
+        let workers = thread::available_parallelism().map_or(1, usize::from);
+        let chunk = self.envs.len().div_ceil(workers).max(1);
+        let stepped: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .envs
+                .chunks_mut(chunk)
+                .zip(action_indices.chunks(chunk))
+                .map(|(envs, indices)| {
+                    scope.spawn(move || {
+                        envs.iter_mut()
+                            .zip(indices)
+                            .map(|(env, index)| env.step(*index))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("stepping doesn't panic"))
+                .collect()
+        });
+        //seeds are handed out in order afterwards so runs repeat however the threads went
+        self.envs
+            .iter_mut()
+            .zip(stepped)
+            .map(|(env, step)| {
+                let (observation, reward, done) = step?;
+                if !done {
+                    return Ok((observation, reward, done));
+                }
+                self.next_seed += 1;
+                let observation = env
+                    .reset(self.next_seed - 1, self.builds.clone())
+                    .expect("the builds were checked when the envs were made");
+                Ok((observation, reward, done))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,1257 @@
+use core::panic;
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    mem,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use action::{Action, IllegalAction, SpellTarget};
+use board::{Board, Entity};
+use effects::Effects;
+use itertools::Itertools;
+use log::{DamageSource, LogEntry, LogEvent};
+use map::{Map, Placement};
+use position::{Direction, Position};
+use projectile::Projectile;
+use replay::ReplayViewer;
+use rng::Rng;
+use schedule::Scheduler;
+use serde::{Deserialize, Serialize};
+use spell::{Spell, SpellInputType, SPELL_POSITION_FILTER};
+use strum::{EnumCount, IntoEnumIterator};
+use wizard::{Team, Wizard};
+
+pub mod action;
+pub mod agent;
+pub mod ai;
+pub mod board;
+pub mod effects;
+pub mod forecast;
+pub mod gym;
+pub mod lobby;
+pub mod log;
+pub mod map;
+pub mod net;
+pub mod position;
+pub mod projectile;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod schedule;
+pub mod spell;
+#[cfg(test)]
+mod test;
+pub mod threat;
+pub mod trajectory;
+pub mod wizard;
+
+pub const MAX_MANA: usize = 100;
+
+pub const MIN_PLAYERS: usize = 2;
+
+///every wizard needs a team to be on in a free-for-all
+pub const MAX_PLAYERS: usize = Team::COUNT;
+
+pub enum Model {
+    Battle(Box<Battle>),
+    SpellSelect(SpellSelect),
+    Replay(Box<ReplayViewer>),
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self::SpellSelect(SpellSelect::default())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpellSelect {
+    pub players: Vec<SpellChoice>,
+    pub seats: Vec<Seat>,
+    pub teams: Vec<Team>,
+    pub placement: Placement,
+}
+
+impl Default for SpellSelect {
+    fn default() -> Self {
+        Self {
+            players: vec![SpellChoice::default(); 2],
+            seats: vec![Seat::Human; 2],
+            teams: vec![Team::Red, Team::Blue],
+            placement: Placement::default(),
+        }
+    }
+}
+
+impl SpellSelect {
+    ///the new player gets a team of their own
+    pub fn add_player(&mut self) {
+        if self.players.len() >= MAX_PLAYERS {
+            return;
+        }
+        let team = Team::iter()
+            .find(|team| !self.teams.contains(team))
+            .expect("there are as many teams as players can be");
+        self.players.push(SpellChoice::default());
+        self.seats.push(Seat::Human);
+        self.teams.push(team);
+    }
+
+    pub fn remove_player(&mut self) {
+        if self.players.len() <= MIN_PLAYERS {
+            return;
+        }
+        self.players.pop();
+        self.seats.pop();
+        self.teams.pop();
+    }
+
+    ///everyone for themselves
+    pub fn free_for_all(&mut self) {
+        self.teams = Team::iter().take(self.players.len()).collect();
+    }
+
+    ///players take turns joining red and blue, so 4 players make a 2v2
+    pub fn two_teams(&mut self) {
+        self.teams = [Team::Red, Team::Blue]
+            .into_iter()
+            .cycle()
+            .take(self.players.len())
+            .collect();
+    }
+
+    ///whether a battle could start with these players, builds aside
+    pub fn is_playable(&self) -> bool {
+        (MIN_PLAYERS..=MAX_PLAYERS).contains(&self.players.len())
+            && self.seats.len() == self.players.len()
+            && self.teams.len() == self.players.len()
+            && !self.teams.iter().all_equal()
+    }
+}
+
+///who makes the decisions for a wizard
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, strum_macros::EnumIs)]
+pub enum Seat {
+    #[default]
+    Human,
+    Ai,
+    ///a bot process started with this command, see `agent::ProcessAgent`
+    Process(String),
+    ///a player connected over the network, see `net::Server`
+    Remote,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpellChoice {
+    pub water: usize,
+    pub fire: usize,
+    pub earth: usize,
+    pub wind: usize,
+    pub unused: usize,
+}
+
+impl Default for SpellChoice {
+    fn default() -> Self {
+        Self {
+            water: 0,
+            fire: 0,
+            earth: 0,
+            wind: 0,
+            unused: 6,
+        }
+    }
+}
+
+impl SpellChoice {
+    ///at most 4 points in each element and none made up
+    pub fn is_valid(&self) -> bool {
+        let elements = [self.water, self.fire, self.earth, self.wind];
+        elements.iter().all(|points| *points <= 4)
+            && elements.iter().sum::<usize>() + self.unused == SpellChoice::default().unused
+    }
+
+    ///valid with every point spent, what a wizard goes into battle with
+    pub fn is_complete(&self) -> bool {
+        self.unused == 0 && self.is_valid()
+    }
+
+    ///every way to spend all the points, at most 4 in each element
+    pub fn all_builds() -> Vec<SpellChoice> {
+        (0..=4)
+            .cartesian_product(0..=4)
+            .cartesian_product(0..=4)
+            .cartesian_product(0..=4)
+            .map(|(((water, fire), earth), wind)| SpellChoice {
+                water,
+                fire,
+                earth,
+                wind,
+                unused: 0,
+            })
+            .filter(SpellChoice::is_complete)
+            .collect()
+    }
+}
+
+///`water,fire,earth,wind` points, which have to add up to a whole build
+impl FromStr for SpellChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((water, fire, earth, wind)) = s
+            .split(',')
+            .map(|points| points.trim().parse::<usize>())
+            .collect_tuple()
+        else {
+            return Err(format!("`{s}` isn't four comma separated numbers"));
+        };
+        let points = |points: Result<usize, _>| points.map_err(|_| format!("`{s}` isn't a build"));
+        let choice = SpellChoice {
+            water: points(water)?,
+            fire: points(fire)?,
+            earth: points(earth)?,
+            wind: points(wind)?,
+            unused: 0,
+        };
+        if !choice.is_valid() {
+            return Err(format!("`{s}` isn't a legal build"));
+        }
+        Ok(choice)
+    }
+}
+
+impl From<&SpellChoice> for Vec<Spell> {
+    fn from(value: &SpellChoice) -> Self {
+        Spell::iter()
+            .filter(|spell| {
+                let (ele, point) = spell.requirement();
+                point
+                    <= match ele {
+                        spell::SpellElement::Water => value.water,
+                        spell::SpellElement::Fire => value.fire,
+                        spell::SpellElement::Earth => value.earth,
+                        spell::SpellElement::Wind => value.wind,
+                    }
+            })
+            .collect()
+    }
+}
+
+///everything needed to recreate the start of a battle
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MatchSetup {
+    pub players: Vec<SpellChoice>,
+    pub seats: Vec<Seat>,
+    pub teams: Vec<Team>,
+    pub map: Map,
+    pub placement: Placement,
+    pub seed: u64,
+}
+
+impl MatchSetup {
+    pub fn new(spell_select: &SpellSelect) -> Self {
+        Self {
+            players: spell_select.players.clone(),
+            seats: spell_select.seats.clone(),
+            teams: spell_select.teams.clone(),
+            map: Map::default(),
+            placement: spell_select.placement,
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_nanos() as u64)
+                .unwrap_or_default(),
+        }
+    }
+
+    ///teams take turns, each sending its next wizard in seat order, so 2v2 goes red, blue, red,
+    ///blue and a team that runs out of wizards is skipped
+    pub fn turn_order(&self) -> Vec<usize> {
+        let sides = self.teams.iter().unique().collect_vec();
+        let members = sides
+            .iter()
+            .map(|side| {
+                (0..self.teams.len())
+                    .filter(|i| self.teams[*i] == **side)
+                    .collect_vec()
+            })
+            .collect_vec();
+        let rounds = members.iter().map(Vec::len).max().unwrap_or(0);
+        (0..rounds)
+            .flat_map(|round| {
+                members
+                    .iter()
+                    .filter_map(move |team| team.get(round).copied())
+            })
+            .collect()
+    }
+
+    ///who picks a starting tile, in the order they pick
+    pub fn placers(&self) -> Vec<usize> {
+        let order = self.turn_order();
+        match self.placement {
+            Placement::Fixed | Placement::Random => vec![],
+            Placement::Chosen => order,
+            Placement::Mirrored => order
+                .into_iter()
+                .filter(|&i| Some(&self.teams[i]) == self.teams.first())
+                .collect(),
+        }
+    }
+
+    ///where everyone starts before any tiles are picked
+    fn spawns(&self) -> Vec<Position> {
+        let spawns = self.map.spawns(&self.teams);
+        if self.placement != Placement::Random {
+            return spawns;
+        }
+        let mut rng = Rng::new(self.seed);
+        let mut taken: Vec<Position> = vec![];
+        for (wizard, spawn) in spawns.iter().enumerate() {
+            let free = self
+                .map
+                .spawn_zone(&self.teams, wizard)
+                .into_iter()
+                .filter(|tile| !taken.contains(tile))
+                .collect_vec();
+            taken.push(match free.len() {
+                0 => *spawn,
+                n => free[rng.below(n)],
+            });
+        }
+        taken
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Battle {
+    setup: MatchSetup,
+    ///every action taken so far, in order
+    actions: Vec<Action>,
+    #[serde(skip)]
+    board: Board,
+    wizards: Vec<Wizard>,
+    current_player: usize,
+    schedule: Scheduler,
+    ///tiles picked so far while wizards choose where to start, empty once the first turn began
+    placements: Vec<Option<Position>>,
+    projectiles: Vec<Projectile>,
+    #[serde(skip)]
+    valid_move_positions: HashSet<Position>,
+    turn: usize,
+    has_moved: bool,
+    has_cast: bool,
+    log: Vec<LogEntry>,
+    ///snapshots from before each action this turn that can still be taken back
+    #[serde(skip)]
+    undo_stack: Vec<Battle>,
+}
+
+///how a projectile's move onto the next tile went
+enum ProjectileStep {
+    Moved,
+    ///something it can't hurt is in the way
+    Blocked,
+    ///it hit a wizard and is gone
+    Hit,
+}
+
+impl Battle {
+    pub fn new(spell_select: &SpellSelect) -> Self {
+        Self::from_setup(MatchSetup::new(spell_select))
+    }
+
+    pub fn from_setup(setup: MatchSetup) -> Self {
+        let wizards = setup
+            .players
+            .iter()
+            .zip(&setup.teams)
+            .zip(setup.spawns())
+            .map(|((spell_choice, team), position)| Wizard {
+                team: *team,
+                health: 100,
+                mana: 100,
+                effects: vec![],
+                position,
+                spells: spell_choice.into(),
+                rest_streak: 0,
+            })
+            .collect_vec();
+        let placers = setup.placers();
+        let first = placers.first().copied().unwrap_or(0);
+        let schedule = Scheduler::new(setup.turn_order());
+        let placements = match placers.len() {
+            0 => vec![],
+            _ => vec![None; wizards.len()],
+        };
+        let projectiles = vec![];
+        let mut temp = Self {
+            setup,
+            actions: vec![],
+            board: Board::new(&wizards, &projectiles),
+            wizards,
+            projectiles,
+            current_player: first,
+            schedule,
+            placements,
+            valid_move_positions: HashSet::new(),
+            turn: 0,
+            has_moved: false,
+            has_cast: false,
+            log: vec![],
+            undo_stack: vec![],
+        };
+        if !temp.is_placing() {
+            temp.start_next_turn();
+        }
+        temp
+    }
+
+    pub fn is_legal(&self, action: Action) -> Result<(), IllegalAction> {
+        let illegal = |reason| Err(IllegalAction { action, reason });
+        if self.is_over() {
+            return illegal("the battle is over");
+        }
+        //actions can come from bots and clients, whose tiles may be anywhere
+        let tile = match action {
+            Action::Place(pos)
+            | Action::Move(pos)
+            | Action::Cast(_, SpellTarget::Position(pos)) => Some(pos),
+            Action::Cast(..) | Action::EndTurn => None,
+        };
+        if tile.is_some_and(|pos| !Board::is_in_bounds(pos)) {
+            return illegal("that tile is off the board");
+        }
+        if self.is_placing() {
+            return match action {
+                Action::Place(tile) if self.can_place(tile) => Ok(()),
+                Action::Place(_) => illegal("can't start on that tile"),
+                _ => illegal("everyone has to pick a starting tile first"),
+            };
+        }
+        match action {
+            Action::Place(_) => illegal("the battle has already started"),
+            Action::Move(_) if self.has_moved => illegal("already moved this turn"),
+            Action::Move(pos) if !self.wizard_can_move(pos) => illegal("can't reach that tile"),
+            Action::Move(_) => Ok(()),
+            Action::Cast(..) if self.has_cast => illegal("already cast a spell this turn"),
+            Action::Cast(i, target) => {
+                let wiz = self.get_current_wizard();
+                let Some(spell) = wiz.spells.get(i) else {
+                    return illegal("no such spell");
+                };
+                if wiz.mana < spell.mana_cost() {
+                    return illegal("not enough mana");
+                }
+                match (spell.spell_input_type(), target) {
+                    (SpellInputType::None(_), SpellTarget::None)
+                    | (SpellInputType::Direction(_), SpellTarget::Direction(_)) => Ok(()),
+                    (SpellInputType::Position(j, _), SpellTarget::Position(pos)) => {
+                        if SPELL_POSITION_FILTER[*j](self, pos) {
+                            Ok(())
+                        } else {
+                            illegal("can't target that tile")
+                        }
+                    }
+                    _ => illegal("wrong kind of target for this spell"),
+                }
+            }
+            Action::EndTurn => Ok(()),
+        }
+    }
+
+    pub fn legal_actions(&self) -> Vec<Action> {
+        if self.is_over() {
+            return vec![];
+        }
+        if self.is_placing() {
+            return self
+                .placement_tiles()
+                .into_iter()
+                .map(Action::Place)
+                .collect();
+        }
+        let mut actions = vec![Action::EndTurn];
+        if !self.has_moved {
+            actions.extend(
+                self.valid_move_positions
+                    .iter()
+                    .map(|pos| Action::Move(*pos)),
+            );
+        }
+        if !self.has_cast {
+            let wiz = self.get_current_wizard();
+            wiz.spells
+                .iter()
+                .enumerate()
+                .filter(|(_, spell)| wiz.mana >= spell.mana_cost())
+                .for_each(|(i, spell)| {
+                    actions.extend(
+                        spell
+                            .targets(self)
+                            .into_iter()
+                            .map(|target| Action::Cast(i, target)),
+                    )
+                });
+        }
+        actions
+    }
+
+    ///the team left standing, once every other team is dead
+    pub fn winner(&self) -> Option<Team> {
+        self.wizards
+            .iter()
+            .filter(|wiz| !wiz.is_dead())
+            .map(|wiz| wiz.team)
+            .all_equal_value()
+            .ok()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some() || self.wizards.iter().all(|wiz| wiz.is_dead())
+    }
+
+    pub fn current_seat(&self) -> &Seat {
+        self.setup
+            .seats
+            .get(self.current_player)
+            .unwrap_or(&Seat::Human)
+    }
+
+    pub fn wizards(&self) -> &[Wizard] {
+        &self.wizards
+    }
+
+    pub fn projectiles(&self) -> &[Projectile] {
+        &self.projectiles
+    }
+
+    ///the only way the battle moves forward, so that every change ends up in `actions`
+    pub fn apply(&mut self, action: Action) -> Result<(), IllegalAction> {
+        self.is_legal(action)?;
+        let mut undo_stack = mem::take(&mut self.undo_stack);
+        if self.reveals_information(action) {
+            undo_stack.clear();
+        } else {
+            undo_stack.push(self.clone());
+        }
+        self.take(action);
+        self.undo_stack = undo_stack;
+        Ok(())
+    }
+
+    ///like `apply` but without a snapshot to undo it, for searches that throw the battle away
+    ///afterwards anyway
+    fn apply_without_undo(&mut self, action: Action) -> Result<(), IllegalAction> {
+        self.is_legal(action)?;
+        self.take(action);
+        Ok(())
+    }
+
+    fn take(&mut self, action: Action) {
+        match action {
+            Action::Move(pos) => self.move_current_wizard_to(pos),
+            Action::Cast(i, target) => self.cast_spell(i, target),
+            Action::EndTurn => self.end_turn(),
+            Action::Place(tile) => self.place(tile),
+        }
+        self.actions.push(action);
+    }
+
+    ///actions that hand over the turn, reveal hidden information or roll something random can't
+    ///be taken back, since the player would get to act on what they learned
+    fn reveals_information(&self, action: Action) -> bool {
+        match action {
+            Action::EndTurn | Action::Place(_) => true,
+            Action::Move(_) | Action::Cast(..) => false,
+        }
+    }
+
+    ///what a player who is out of time or ideas does: end the turn, or start as close to the
+    ///default spawn as they can
+    pub fn pass_action(&self) -> Action {
+        if !self.is_placing() {
+            return Action::EndTurn;
+        }
+        let spawn = self.setup.map.spawns(&self.setup.teams)[self.current_player];
+        let tile = match self.can_place(spawn) {
+            true => spawn,
+            false => self
+                .placement_tiles()
+                .into_iter()
+                .min_by_key(|tile| spawn.x.abs_diff(tile.x) + spawn.y.abs_diff(tile.y))
+                .unwrap_or(spawn),
+        };
+        Action::Place(tile)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    ///takes back the last action of this turn, returns whether there was one
+    pub fn undo(&mut self) -> bool {
+        let Some(mut previous) = self.undo_stack.pop() else {
+            return false;
+        };
+        previous.undo_stack = mem::take(&mut self.undo_stack);
+        *self = previous;
+        true
+    }
+
+    fn cast_spell(&mut self, spell_index: usize, target: SpellTarget) {
+        let caster = self.current_player;
+        let wiz = &mut self.wizards[caster];
+        let spell = wiz.spells[spell_index];
+        wiz.mana -= spell.mana_cost();
+        self.has_cast = true;
+        self.log_event(LogEvent::SpellCast {
+            wizard: caster,
+            spell,
+        });
+        let area = spell.area(self, target);
+        match spell {
+            Spell::IncreasedCirculation => {
+                self.apply_effect(caster, Effects::Circulation, caster, 3)
+            }
+            Spell::WaterSpear | Spell::Flame => {
+                for wizard in self.enemies_in(&area) {
+                    self.damage_wizard(wizard, spell.damage(), DamageSource::Spell(spell));
+                }
+            }
+            Spell::ManaDrain => {
+                for wizard in self.enemies_in(&area) {
+                    let mana = self.wizards[wizard].mana.min(30);
+                    self.wizards[wizard].mana -= mana;
+                    let caster_mana = &mut self.wizards[caster].mana;
+                    *caster_mana = (*caster_mana + mana).min(MAX_MANA);
+                    self.log_event(LogEvent::ManaDrained {
+                        wizard,
+                        by: caster,
+                        mana,
+                    });
+                }
+            }
+            Spell::Stagnation => {
+                for wizard in self.wizards_in(&area) {
+                    self.apply_effect(wizard, Effects::Stagnant, caster, 2);
+                }
+            }
+            Spell::Explosion => self.blast(&area, spell.damage(), spell),
+            Spell::AuraOfFire => self.apply_effect(caster, Effects::AuraOfFire, caster, 3),
+            Spell::StoneSkin => self.apply_effect(caster, Effects::StoneSkin, caster, 3),
+            Spell::Spikes | Spell::Wall => {
+                let owner = self.wizards[caster].team;
+                //they grow out of empty ground only, so nobody gets impaled by one going up
+                let empty = area
+                    .into_iter()
+                    .filter(|tile| self.get_entity_at(*tile).is_none())
+                    .collect_vec();
+                for position in empty {
+                    let projectile = spell.projectile(owner, caster, position, Direction::Up);
+                    if let Some(projectile) = projectile {
+                        self.spawn_projectile(projectile);
+                    }
+                }
+            }
+            Spell::Fireball | Spell::Boulder | Spell::WindBolt => {
+                let SpellTarget::Direction(direction) = target else {
+                    unreachable!("casts are checked against the spell's input type");
+                };
+                let wiz = &self.wizards[caster];
+                let position = wiz.position.move_in_direction(direction);
+                if let Some(projectile) = spell.projectile(wiz.team, caster, position, direction) {
+                    self.spawn_projectile(projectile);
+                }
+            }
+            Spell::Glide => {
+                let SpellTarget::Direction(direction) = target else {
+                    unreachable!("casts are checked against the spell's input type");
+                };
+                self.force_move(Entity::Wizard(caster), direction, 7, None);
+            }
+            Spell::RepulsiveBlast => {
+                //the blast goes off first, the tiles it covers are where projectiles are now
+                self.blast(&area, spell.damage(), spell);
+                let center = self.wizards[caster].position;
+                //the furthest go first so they don't block the ones behind them
+                let pushed = (0..self.wizards.len())
+                    .filter(|&w| {
+                        let wiz = &self.wizards[w];
+                        !wiz.is_dead()
+                            && wiz.team != self.wizards[caster].team
+                            && center.dist(wiz.position).mag() <= 6
+                    })
+                    .sorted_by_key(|&w| Reverse(center.dist(self.wizards[w].position).mag()))
+                    .collect_vec();
+                for wizard in pushed {
+                    if let Some(away) = center.direction_to(self.wizards[wizard].position) {
+                        self.force_move(Entity::Wizard(wizard), away, 4, None);
+                    }
+                }
+            }
+            Spell::Tornado => self.apply_effect(caster, Effects::Tornado, caster, 2),
+        }
+        //effects and conjured obstacles change where the caster can still go
+        self.update_valid_move_positons();
+    }
+
+    ///damages the current wizard's enemies and their projectiles on these tiles
+    fn blast(&mut self, tiles: &[Position], damage: usize, spell: Spell) {
+        for wizard in self.enemies_in(tiles) {
+            self.damage_wizard(wizard, damage, DamageSource::Spell(spell));
+        }
+        let team = self.get_current_wizard().team;
+        //back to front so removing one doesn't shift the others
+        let hit = tiles
+            .iter()
+            .filter_map(|tile| match self.get_entity_at(*tile) {
+                Some(Entity::Projectile(p)) if self.projectiles[p].owner != team => Some(p),
+                _ => None,
+            })
+            .sorted()
+            .rev()
+            .collect_vec();
+        for p in hit {
+            self.damage_projectile(p, damage);
+        }
+    }
+
+    fn damage_projectile(&mut self, p: usize, damage: usize) {
+        let proj = &mut self.projectiles[p];
+        proj.take_damage(damage);
+        if proj.damage == 0 {
+            let (position, projectile_type) = (proj.position, proj.projectile_type);
+            self.remove_projectile(p);
+            self.log_event(LogEvent::ProjectileDestroyed {
+                position,
+                projectile_type,
+            });
+        }
+    }
+
+    ///puts a projectile down, one shot into an enemy hits them straight away and one landing on
+    ///anything else fizzles
+    fn spawn_projectile(&mut self, projectile: Projectile) {
+        match self.get_entity_at(projectile.position) {
+            None => {
+                self.projectiles.push(projectile);
+                self.board = Board::new(&self.wizards, &self.projectiles);
+            }
+            Some(Entity::Wizard(w))
+                if !self.wizards[w].is_dead() && projectile.hurts(&self.wizards[w]) =>
+            {
+                let source = DamageSource::Projectile(projectile.projectile_type);
+                self.damage_wizard(w, projectile.damage, source);
+            }
+            Some(_) => {}
+        }
+    }
+
+    ///moves the projectiles the wizard is in charge of at the end of their turn and burns down
+    ///their lifetime, so each one moves once a round however big the teams are
+    fn advance_projectiles(&mut self, wizard: usize) {
+        let mut p = 0;
+        while p < self.projectiles.len() {
+            if self.projectile_mover(p) != Some(wizard) || self.advance_projectile(p) {
+                p += 1;
+            }
+        }
+    }
+
+    ///whose turn end moves the projectile: its caster's, or once they're dead the first living
+    ///teammate's so it doesn't hang around forever
+    fn projectile_mover(&self, p: usize) -> Option<usize> {
+        let proj = &self.projectiles[p];
+        std::iter::once(proj.caster)
+            .chain(0..self.wizards.len())
+            .find(|&w| self.wizards[w].team == proj.owner && !self.wizards[w].is_dead())
+    }
+
+    ///returns whether the projectile is still around afterwards
+    fn advance_projectile(&mut self, p: usize) -> bool {
+        for _ in 0..self.projectiles[p].speed {
+            match self.step_projectile(p) {
+                ProjectileStep::Moved => {}
+                ProjectileStep::Blocked => break,
+                ProjectileStep::Hit => return false,
+            }
+        }
+        self.burn_projectile(p)
+    }
+
+    ///moves the projectile a single tile, hitting whoever is there
+    fn step_projectile(&mut self, p: usize) -> ProjectileStep {
+        let proj = &self.projectiles[p];
+        let direction = match proj.guiding {
+            true => self.homing_direction(proj).unwrap_or(proj.direction),
+            false => proj.direction,
+        };
+        let (from, to) = (proj.position, proj.position.move_in_direction(direction));
+        self.projectiles[p].direction = direction;
+        match self.get_entity_at(to) {
+            None => {
+                self.board.swap_enttities(from, to);
+                self.projectiles[p].position = to;
+                ProjectileStep::Moved
+            }
+            Some(Entity::Wizard(w))
+                if !self.wizards[w].is_dead() && self.projectiles[p].hurts(&self.wizards[w]) =>
+            {
+                let proj = &self.projectiles[p];
+                let (damage, source) =
+                    (proj.damage, DamageSource::Projectile(proj.projectile_type));
+                self.damage_wizard(w, damage, source);
+                self.remove_projectile(p);
+                ProjectileStep::Hit
+            }
+            //anything else is in the way
+            Some(_) => ProjectileStep::Blocked,
+        }
+    }
+
+    ///burns down a turn of the projectile's lifetime, returns whether it's still around
+    fn burn_projectile(&mut self, p: usize) -> bool {
+        let proj = &mut self.projectiles[p];
+        proj.lifetime = proj.lifetime.saturating_sub(1);
+        if proj.lifetime == 0 {
+            self.remove_projectile(p);
+            return false;
+        }
+        true
+    }
+
+    ///the way towards the closest enemy, none once there's nobody to chase
+    fn homing_direction(&self, proj: &Projectile) -> Option<Direction> {
+        let target = self
+            .wizards
+            .iter()
+            .filter(|wiz| !wiz.is_dead() && proj.hurts(wiz))
+            .min_by_key(|wiz| proj.position.dist(wiz.position).mag())?;
+        proj.position.direction_to(target.position)
+    }
+
+    ///shoves a wizard or projectile up to `tiles` tiles one at a time, stopping in front of
+    ///anything it can't pass through, with `collision_damage` hurting both it and what it ran
+    ///into. wizards run over passable projectiles on the way like they would walking, projectiles
+    ///stop at anything. the board wraps around, so there's no edge to run into
+    pub fn force_move(
+        &mut self,
+        entity: Entity,
+        direction: Direction,
+        tiles: usize,
+        collision_damage: Option<usize>,
+    ) {
+        for _ in 0..tiles {
+            let from = match entity {
+                Entity::Wizard(w) => self.wizards[w].position,
+                Entity::Projectile(p) => self.projectiles[p].position,
+            };
+            let to = from.move_in_direction(direction);
+            let obstacle = match (entity, self.get_entity_at(to)) {
+                (_, None) => None,
+                (Entity::Wizard(w), Some(Entity::Projectile(p)))
+                    if self.projectiles[p].passable =>
+                {
+                    let proj = &self.projectiles[p];
+                    if proj.hurts(&self.wizards[w]) {
+                        let (damage, source) =
+                            (proj.damage, DamageSource::Projectile(proj.projectile_type));
+                        self.damage_wizard(w, damage, source);
+                    }
+                    self.remove_projectile(p);
+                    None
+                }
+                (_, Some(obstacle)) => Some(obstacle),
+            };
+            if let Some(obstacle) = obstacle {
+                if let Some(damage) = collision_damage {
+                    self.collide([entity, obstacle], damage);
+                }
+                break;
+            }
+            self.board.swap_enttities(from, to);
+            match entity {
+                Entity::Wizard(wizard) => {
+                    self.wizards[wizard].position = to;
+                    self.log_event(LogEvent::Slid { wizard, from, to });
+                    if self.wizards[wizard].is_dead() {
+                        break;
+                    }
+                }
+                Entity::Projectile(p) => {
+                    self.projectiles[p].position = to;
+                    self.log_event(LogEvent::ProjectileSlid {
+                        projectile_type: self.projectiles[p].projectile_type,
+                        from,
+                        to,
+                    });
+                }
+            }
+        }
+        self.update_valid_move_positons();
+    }
+
+    fn collide(&mut self, entities: [Entity; 2], damage: usize) {
+        let mut projectiles = vec![];
+        for entity in entities {
+            match entity {
+                Entity::Wizard(w) => self.damage_wizard(w, damage, DamageSource::Collision),
+                Entity::Projectile(p) => projectiles.push(p),
+            }
+        }
+        //back to front so removing one doesn't shift the other
+        for p in projectiles.into_iter().sorted().rev() {
+            self.damage_projectile(p, damage);
+        }
+    }
+
+    ///the living wizards standing on these tiles
+    fn wizards_in(&self, tiles: &[Position]) -> Vec<usize> {
+        tiles
+            .iter()
+            .filter_map(|tile| match self.get_entity_at(*tile) {
+                Some(Entity::Wizard(w)) if !self.wizards[w].is_dead() => Some(w),
+                _ => None,
+            })
+            .collect()
+    }
+
+    ///the living wizards on these tiles that aren't on the current wizard's team
+    fn enemies_in(&self, tiles: &[Position]) -> Vec<usize> {
+        let team = self.get_current_wizard().team;
+        self.wizards_in(tiles)
+            .into_iter()
+            .filter(|&w| self.wizards[w].team != team)
+            .collect()
+    }
+
+    pub fn move_wizard_to(&mut self, wiz_i: usize, pos: Position) {
+        let from = self.wizards[wiz_i].position;
+        match self.board.get_entity_at(pos) {
+            None => {}
+            Some(Entity::Projectile(p)) => {
+                let proj = &self.projectiles[p];
+                if !proj.passable {
+                    panic!("trying to move into an impassible projectile");
+                }
+                //a friendly projectile is picked up without hurting
+                if proj.hurts(&self.wizards[wiz_i]) {
+                    let (damage, source) =
+                        (proj.damage, DamageSource::Projectile(proj.projectile_type));
+                    self.damage_wizard(wiz_i, damage, source);
+                }
+                self.remove_projectile(p);
+            }
+            Some(Entity::Wizard(w)) if w != wiz_i => {
+                panic!("trying to move a wizard into another wizard")
+            }
+            Some(Entity::Wizard(_)) => {}
+        }
+        if from != pos {
+            self.board.remove_entity_at(pos);
+            self.board.swap_enttities(from, pos);
+        }
+        self.wizards[wiz_i].position = pos;
+        self.log_event(LogEvent::Move {
+            wizard: wiz_i,
+            from,
+            to: pos,
+        });
+        self.update_valid_move_positons();
+    }
+
+    fn move_current_wizard_to(&mut self, pos: Position) {
+        self.has_moved = true;
+        self.move_wizard_to(self.current_player, pos);
+    }
+
+    fn remove_projectile(&mut self, p: usize) {
+        self.projectiles.remove(p);
+        self.board = Board::new(&self.wizards, &self.projectiles);
+    }
+
+    pub fn damage_wizard(&mut self, wiz_i: usize, damage: usize, source: DamageSource) {
+        let wiz = &mut self.wizards[wiz_i];
+        let was_dead = wiz.is_dead();
+        let amount = wiz.take_damage(damage);
+        let (position, is_dead) = (wiz.position, wiz.is_dead());
+        self.log_event(LogEvent::WizardDamaged {
+            wizard: wiz_i,
+            position,
+            amount,
+            source,
+        });
+        if is_dead && !was_dead {
+            self.log_event(LogEvent::WizardDied {
+                wizard: wiz_i,
+                position,
+            });
+        }
+    }
+
+    fn place(&mut self, tile: Position) {
+        self.placements[self.current_player] = Some(tile);
+        //only that a tile was picked, so the others can't see where
+        self.log_event(LogEvent::Placed {
+            wizard: self.current_player,
+        });
+        let next = self
+            .setup
+            .placers()
+            .into_iter()
+            .find(|&i| self.placements[i].is_none());
+        match next {
+            Some(next) => self.current_player = next,
+            None => self.reveal_placements(),
+        }
+    }
+
+    ///moves everyone onto their tiles once all have picked and starts the first turn
+    fn reveal_placements(&mut self) {
+        let mut starts = mem::take(&mut self.placements);
+        let teams = self.setup.teams.clone();
+        let leaders = self.setup.placers();
+        for wizard in 0..starts.len() {
+            if starts[wizard].is_some() {
+                continue;
+            }
+            //mirrored wizards copy whoever sits in the same spot on the first team
+            let zone = self.setup.map.spawn_zone(&teams, wizard);
+            let member = teams[..wizard]
+                .iter()
+                .filter(|team| **team == teams[wizard])
+                .count();
+            let leader = leaders[member % leaders.len()];
+            let free = |tile: &Position| zone.contains(tile) && !starts.contains(&Some(*tile));
+            let tile = starts[leader]
+                .and_then(|tile| self.setup.map.mirror(&teams, leader, wizard, tile))
+                .filter(free)
+                .or_else(|| zone.iter().copied().find(free))
+                .unwrap_or(self.wizards[wizard].position);
+            starts[wizard] = Some(tile);
+        }
+        for (wizard, tile) in starts.into_iter().enumerate() {
+            let position = tile.unwrap_or(self.wizards[wizard].position);
+            self.wizards[wizard].position = position;
+            self.log_event(LogEvent::Spawned { wizard, position });
+        }
+        self.board = Board::new(&self.wizards, &self.projectiles);
+        self.start_next_turn();
+    }
+
+    ///recovers mana, ticks down effects and hands the turn to the next living wizard
+    fn end_turn(&mut self) {
+        let wiz_i = self.current_player;
+        self.end_effects(wiz_i);
+        let wiz = &mut self.wizards[wiz_i];
+        let recovered = if self.has_moved {
+            wiz.rest_streak = 0;
+            10
+        } else {
+            wiz.rest_streak += 1;
+            match wiz.rest_streak {
+                1 => 20,
+                2 => 30,
+                _ => 50,
+            }
+        };
+        let recovered = wiz
+            .effects
+            .iter()
+            .fold(recovered, |mana, e| e.kind.modify_mana_gain(mana))
+            .min(MAX_MANA - wiz.mana.min(MAX_MANA));
+        wiz.mana += recovered;
+        self.log_event(LogEvent::ManaRecovered {
+            wizard: wiz_i,
+            mana: recovered,
+        });
+        self.tick_effects(wiz_i, false);
+        self.advance_projectiles(wiz_i);
+
+        self.turn += 1;
+        self.has_moved = false;
+        self.has_cast = false;
+        self.start_next_turn();
+    }
+
+    ///hands the turn to whoever the schedule says is next, logging anyone who had to sit out
+    fn start_next_turn(&mut self) {
+        let wizards = &self.wizards;
+        let mut stagnant = self.stagnant_turns();
+        let (next, skipped) = self
+            .schedule
+            .next_turn(|i| !wizards[i].is_dead(), &mut stagnant)
+            .unwrap_or((self.current_player, vec![]));
+        //only turns lost to stagnation wear it off, not ones passed up by a delay
+        let mut stagnated = self
+            .stagnant_turns()
+            .into_iter()
+            .zip(stagnant)
+            .map(|(before, after)| before - after)
+            .collect_vec();
+        for wizard in skipped {
+            self.log_event(LogEvent::TurnSkipped { wizard });
+            if stagnated[wizard] > 0 {
+                stagnated[wizard] -= 1;
+                self.tick_effects(wizard, true);
+            }
+        }
+        self.current_player = next;
+        self.log_event(LogEvent::TurnStart { wizard: next });
+        self.update_valid_move_positons();
+    }
+
+    ///the wizard sits out their next `turns` turns, shown as stagnation
+    pub fn skip_turns(&mut self, wizard: usize, turns: usize) {
+        self.apply_effect(wizard, Effects::Stagnant, self.current_player, turns);
+    }
+
+    ///the wizard's next turn comes once `turns` other turns have been taken
+    pub fn delay_turn(&mut self, wizard: usize, turns: usize) {
+        self.schedule.delay(wizard, turns);
+    }
+
+    ///the wizard gets an extra turn right after this one
+    pub fn insert_turn(&mut self, wizard: usize) {
+        self.schedule.insert(wizard);
+    }
+
+    ///who acts in the next `count` turns, not counting this one
+    pub fn upcoming_turns(&self, count: usize) -> Vec<usize> {
+        if self.is_placing() {
+            return vec![];
+        }
+        self.schedule
+            .upcoming(count, |i| !self.wizards[i].is_dead(), self.stagnant_turns())
+    }
+
+    ///how many turns each wizard still has to sit out
+    fn stagnant_turns(&self) -> Vec<usize> {
+        self.wizards
+            .iter()
+            .map(|wiz| wiz.effect_turns(Effects::Stagnant))
+            .collect()
+    }
+
+    fn log_event(&mut self, event: LogEvent) {
+        self.log.push(LogEntry {
+            turn: self.turn,
+            actor: self.current_player,
+            event,
+        });
+    }
+
+    pub fn log(&self) -> &[LogEntry] {
+        &self.log
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn current_player(&self) -> usize {
+        self.current_player
+    }
+
+    pub fn setup(&self) -> &MatchSetup {
+        &self.setup
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    pub fn get_entity_at(&self, position: Position) -> Option<Entity> {
+        self.board.get_entity_at(position)
+    }
+
+    ///the team's colour, numbered when the team has more than one wizard
+    pub fn wizard_name(&self, wizard: usize) -> String {
+        let team = self.wizards[wizard].team;
+        let teammates = self.wizards.iter().filter(|wiz| wiz.team == team).count();
+        if teammates == 1 {
+            return format!("{team} wizard");
+        }
+        let number = self.wizards[..wizard]
+            .iter()
+            .filter(|wiz| wiz.team == team)
+            .count()
+            + 1;
+        format!("{team} wizard {number}")
+    }
+
+    pub fn get_wizard(&self, entity: usize) -> &Wizard {
+        &self.wizards[entity]
+    }
+
+    pub fn get_current_wizard(&self) -> &Wizard {
+        &self.wizards[self.current_player]
+    }
+
+    pub fn get_projectile(&self, entity: usize) -> &Projectile {
+        &self.projectiles[entity]
+    }
+
+    ///whether wizards are still picking where to start
+    pub fn is_placing(&self) -> bool {
+        !self.placements.is_empty()
+    }
+
+    ///the tiles the current wizard could still pick to start on
+    pub fn placement_tiles(&self) -> Vec<Position> {
+        if !self.is_placing() {
+            return vec![];
+        }
+        self.setup
+            .map
+            .spawn_zone(&self.setup.teams, self.current_player)
+            .into_iter()
+            .filter(|tile| !self.placements.contains(&Some(*tile)))
+            .collect()
+    }
+
+    pub fn can_place(&self, tile: Position) -> bool {
+        self.placement_tiles().contains(&tile)
+    }
+
+    pub fn wizard_can_move(&self, tile: Position) -> bool {
+        self.valid_move_positions.contains(&tile)
+    }
+
+    fn can_move_from(&mut self, start: Position, depth: usize) {
+        if self.get_entity_at(start).is_some_and(|e| -> bool {
+            match e {
+                Entity::Wizard(w) => self.current_player != w,
+                Entity::Projectile(p) => !self.get_projectile(p).passable,
+            }
+        }) {
+            return;
+        }
+        self.valid_move_positions.insert(start);
+        if depth > 0 {
+            Direction::iter().for_each(|dir| {
+                let end = start.move_in_direction(dir);
+                if !self.valid_move_positions.contains(&end) {
+                    self.can_move_from(end, depth - 1);
+                }
+            });
+        }
+    }
+
+    fn update_valid_move_positons(&mut self) {
+        self.valid_move_positions.drain();
+        if self.has_moved || self.is_placing() {
+            return;
+        }
+
+        let wiz = self.get_current_wizard();
+        let max_distance = wiz
+            .effects
+            .iter()
+            .fold(2, |distance, e| e.kind.modify_movement(distance));
+
+        self.can_move_from(wiz.position, max_distance);
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{map::Map, net::NET_VERSION};
+
+pub const DISCOVERY_PORT: u16 = 4742;
+
+pub const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+///games that weren't announced for this long are assumed to be gone
+const FORGET_AFTER: Duration = Duration::from_secs(4);
+
+///what a host broadcasts about its game, one json datagram each
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub version: u32,
+    pub name: String,
+    ///the tcp port to join on, the address is wherever the announcement came from
+    pub port: u16,
+    pub map: Map,
+    pub free_seats: usize,
+    pub started: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiscoveredGame {
+    pub addr: SocketAddr,
+    pub announcement: Announcement,
+}
+
+///something to call this machine in other people's lobbies
+pub fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "wizard-fight host".to_owned())
+}
+
+pub fn announce_socket() -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    Ok(socket)
+}
+
+///sends to the whole local network, and to this machine in case broadcasts don't come back
+pub fn send_announcement(
+    socket: &UdpSocket,
+    announcement: &Announcement,
+    port: u16,
+) -> io::Result<()> {
+    let datagram = serde_json::to_vec(announcement).map_err(io::Error::other)?;
+    let broadcast = socket.send_to(&datagram, (Ipv4Addr::BROADCAST, port));
+    socket.send_to(&datagram, (Ipv4Addr::LOCALHOST, port))?;
+    broadcast.map(|_| ())
+}
+
+///listens for announcements until dropped
+pub struct Discovery {
+    games: Arc<Mutex<HashMap<SocketAddr, (Announcement, Instant)>>>,
+    closed: Arc<AtomicBool>,
+    port: u16,
+}
+
+impl Discovery {
+    pub fn listen() -> io::Result<Self> {
+        Self::listen_on(DISCOVERY_PORT)
+    }
+
+    ///listens on another port, 0 picks a free one
+    pub fn listen_on(port: u16) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        let port = socket.local_addr()?.port();
+        //so the thread notices being closed
+        socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+        let games = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        {
+            let games = games.clone();
+            let closed = closed.clone();
+            thread::spawn(move || {
+                let mut buffer = [0; 2048];
+                while !closed.load(Ordering::Relaxed) {
+                    let Ok((len, from)) = socket.recv_from(&mut buffer) else {
+                        continue;
+                    };
+                    let Ok(announcement) = serde_json::from_slice::<Announcement>(&buffer[..len])
+                    else {
+                        continue;
+                    };
+                    if announcement.version != NET_VERSION {
+                        continue;
+                    }
+                    let addr = SocketAddr::new(from.ip(), announcement.port);
+                    games
+                        .lock()
+                        .expect("the discovery thread doesn't panic")
+                        .insert(addr, (announcement, Instant::now()));
+                }
+            });
+        }
+        Ok(Self {
+            games,
+            closed,
+            port,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    ///games announced recently, sorted by name
+    pub fn games(&self) -> Vec<DiscoveredGame> {
+        let mut games = self
+            .games
+            .lock()
+            .expect("the discovery thread doesn't panic");
+        games.retain(|_, (_, seen)| seen.elapsed() < FORGET_AFTER);
+        let mut games: Vec<DiscoveredGame> = games
+            .iter()
+            .map(|(addr, (announcement, _))| DiscoveredGame {
+                addr: *addr,
+                announcement: announcement.clone(),
+            })
+            .collect();
+        games.sort_by(|a, b| a.announcement.name.cmp(&b.announcement.name));
+        games
+    }
+}
+
+impl Drop for Discovery {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
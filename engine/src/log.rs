@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIs;
+
+use super::{
+    effects::Effects, position::Position, projectile::ProjectileType, spell::Spell, Battle,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub turn: usize,
+    ///the wizard whose turn it was when this happened
+    pub actor: usize,
+    pub event: LogEvent,
+}
+
+#[derive(Clone, Debug, EnumIs, Serialize, Deserialize)]
+pub enum LogEvent {
+    TurnStart {
+        wizard: usize,
+    },
+    Move {
+        wizard: usize,
+        from: Position,
+        to: Position,
+    },
+    ManaRecovered {
+        wizard: usize,
+        mana: usize,
+    },
+    WizardDamaged {
+        wizard: usize,
+        position: Position,
+        amount: usize,
+        source: DamageSource,
+    },
+    SpellCast {
+        wizard: usize,
+        spell: Spell,
+    },
+    WizardDied {
+        wizard: usize,
+        position: Position,
+    },
+    EffectApplied {
+        wizard: usize,
+        effect: Effects,
+        ///the wizard who put the effect there
+        by: usize,
+        turns: usize,
+    },
+    EffectExpired {
+        wizard: usize,
+        effect: Effects,
+    },
+    ///one tile of being shoved, or gliding
+    Slid {
+        wizard: usize,
+        from: Position,
+        to: Position,
+    },
+    ProjectileSlid {
+        projectile_type: ProjectileType,
+        from: Position,
+        to: Position,
+    },
+    ProjectileDestroyed {
+        position: Position,
+        projectile_type: ProjectileType,
+    },
+    ManaDrained {
+        wizard: usize,
+        ///who the mana went to
+        by: usize,
+        mana: usize,
+    },
+    ///the wizard's turn came up while they had to sit it out
+    TurnSkipped {
+        wizard: usize,
+    },
+    ///a starting tile was picked, where stays hidden until everyone has picked
+    Placed {
+        wizard: usize,
+    },
+    Spawned {
+        wizard: usize,
+        position: Position,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DamageSource {
+    Projectile(ProjectileType),
+    ///spells that hit straight away instead of conjuring something
+    Spell(Spell),
+    ///being shoved into something, or having something shoved into you
+    Collision,
+}
+
+impl LogEvent {
+    pub fn is_damage(&self) -> bool {
+        self.is_wizard_damaged()
+    }
+
+    ///the tiles that should be highlighted when this entry is selected
+    pub fn tiles(&self) -> Vec<Position> {
+        match self {
+            LogEvent::Move { from, to, .. }
+            | LogEvent::Slid { from, to, .. }
+            | LogEvent::ProjectileSlid { from, to, .. } => vec![*from, *to],
+            LogEvent::WizardDamaged { position, .. }
+            | LogEvent::WizardDied { position, .. }
+            | LogEvent::Spawned { position, .. }
+            | LogEvent::ProjectileDestroyed { position, .. } => vec![*position],
+            LogEvent::TurnStart { .. }
+            | LogEvent::ManaRecovered { .. }
+            | LogEvent::SpellCast { .. }
+            | LogEvent::EffectApplied { .. }
+            | LogEvent::EffectExpired { .. }
+            | LogEvent::ManaDrained { .. }
+            | LogEvent::Placed { .. }
+            | LogEvent::TurnSkipped { .. } => {
+                vec![]
+            }
+        }
+    }
+
+    pub fn describe(&self, battle: &Battle) -> String {
+        let name = |w: &usize| battle.wizard_name(*w);
+        match self {
+            LogEvent::TurnStart { wizard } => format!("{}'s turn", name(wizard)),
+            LogEvent::Move { wizard, from, to } => format!(
+                "{} moved from ({}, {}) to ({}, {})",
+                name(wizard),
+                from.x,
+                from.y,
+                to.x,
+                to.y
+            ),
+            LogEvent::ManaRecovered { wizard, mana } => {
+                format!("{} recovered {mana} mana", name(wizard))
+            }
+            LogEvent::WizardDamaged {
+                wizard,
+                amount,
+                source,
+                ..
+            } => format!("{} took {amount} damage from {source}", name(wizard)),
+            LogEvent::SpellCast { wizard, spell } => format!("{} cast {spell:?}", name(wizard)),
+            LogEvent::WizardDied { wizard, .. } => format!("{} died", name(wizard)),
+            LogEvent::EffectApplied {
+                wizard,
+                effect,
+                by,
+                turns,
+            } if by != wizard => format!(
+                "{} has {effect} for {turns} turns from {}",
+                name(wizard),
+                name(by)
+            ),
+            LogEvent::EffectApplied {
+                wizard,
+                effect,
+                turns,
+                ..
+            } => format!("{} has {effect} for {turns} turns", name(wizard)),
+            LogEvent::Slid { wizard, from, to } => format!(
+                "{} slid from ({}, {}) to ({}, {})",
+                name(wizard),
+                from.x,
+                from.y,
+                to.x,
+                to.y
+            ),
+            LogEvent::ProjectileSlid {
+                projectile_type,
+                from,
+                to,
+            } => format!(
+                "a {projectile_type} slid from ({}, {}) to ({}, {})",
+                from.x, from.y, to.x, to.y
+            ),
+            LogEvent::ProjectileDestroyed {
+                position,
+                projectile_type,
+            } => format!(
+                "a {projectile_type} at ({}, {}) was destroyed",
+                position.x, position.y
+            ),
+            LogEvent::ManaDrained { wizard, by, mana } => {
+                format!("{} drained {mana} mana from {}", name(by), name(wizard))
+            }
+            LogEvent::EffectExpired { wizard, effect } => {
+                format!("{}'s {effect} wore off", name(wizard))
+            }
+            LogEvent::TurnSkipped { wizard } => format!("{} sat out a turn", name(wizard)),
+            LogEvent::Placed { wizard } => format!("{} picked a starting tile", name(wizard)),
+            LogEvent::Spawned { wizard, position } => format!(
+                "{} starts at ({}, {})",
+                name(wizard),
+                position.x,
+                position.y
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for DamageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamageSource::Projectile(projectile_type) => write!(f, "a {projectile_type}"),
+            DamageSource::Spell(spell) => write!(f, "{spell:?}"),
+            DamageSource::Collision => write!(f, "a collision"),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::f64::consts::{PI, TAU};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use super::{
+    board::{HEIGHT, WIDTH},
+    position::Position,
+    wizard::Team,
+};
+
+///how far from the default spawns a wizard may start
+const SPAWN_ZONE_RADIUS: usize = 2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum Map {
+    #[default]
+    Field,
+}
+
+///how wizards get their starting tiles before the first turn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum Placement {
+    ///everyone starts on the map's default spawns
+    Fixed,
+    ///everyone picks a tile in their spawn zone, hidden until all have picked
+    #[default]
+    Chosen,
+    ///a seeded pick in each spawn zone
+    Random,
+    ///the first team picks and the others copy it, turned to their side of the map
+    Mirrored,
+}
+
+impl Map {
+    ///where each wizard starts, teams spread out around the middle with teammates side by side
+    pub fn spawns(self, teams: &[Team]) -> Vec<Position> {
+        match self {
+            Map::Field => {
+                let sides = teams.iter().unique().collect_vec();
+                teams
+                    .iter()
+                    .enumerate()
+                    .map(|(i, team)| {
+                        let side = sides.iter().position(|side| *side == team).unwrap_or(0);
+                        let members = teams.iter().filter(|other| *other == team).count();
+                        let member = teams[..i].iter().filter(|other| *other == team).count();
+                        //the first team starts on the left, the others follow around an ellipse
+                        let (sin, cos) = (PI + TAU * side as f64 / sides.len() as f64).sin_cos();
+                        let offset = 2.0 * member as f64 - (members - 1) as f64;
+                        let x = 14.0 + 12.0 * cos - sin * offset;
+                        let y = 9.0 + 7.0 * sin + cos * offset;
+                        (x.round() as usize, y.round() as usize).into()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    ///the tiles a wizard may start on, shared with their teammates
+    pub fn spawn_zone(self, teams: &[Team], wizard: usize) -> Vec<Position> {
+        let team_spawns = self
+            .spawns(teams)
+            .into_iter()
+            .zip(teams)
+            .filter(|(_, team)| **team == teams[wizard])
+            .map(|(spawn, _)| spawn)
+            .collect_vec();
+        (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| Position::from((x, y))))
+            .filter(|tile| {
+                team_spawns.iter().any(|spawn| {
+                    spawn.x.abs_diff(tile.x).max(spawn.y.abs_diff(tile.y)) <= SPAWN_ZONE_RADIUS
+                })
+            })
+            .collect()
+    }
+
+    ///where `wizard` starts if it copies `leader` standing on `tile`, none if that's off the map
+    pub fn mirror(
+        self,
+        teams: &[Team],
+        leader: usize,
+        wizard: usize,
+        tile: Position,
+    ) -> Option<Position> {
+        match self {
+            Map::Field => {
+                let spawns = self.spawns(teams);
+                let sides = teams.iter().unique().collect_vec();
+                let side = |i: usize| {
+                    sides
+                        .iter()
+                        .position(|side| **side == teams[i])
+                        .unwrap_or(0)
+                };
+                //turning by the angle between the two sides keeps "towards the middle" the same
+                let (sin, cos) = (TAU * (side(wizard) as f64 - side(leader) as f64)
+                    / sides.len() as f64)
+                    .sin_cos();
+                let dx = tile.x as f64 - spawns[leader].x as f64;
+                let dy = tile.y as f64 - spawns[leader].y as f64;
+                let x = spawns[wizard].x as f64 + (dx * cos - dy * sin).round();
+                let y = spawns[wizard].y as f64 + (dx * sin + dy * cos).round();
+                (x >= 0.0 && y >= 0.0 && (x as usize) < WIDTH && (y as usize) < HEIGHT)
+                    .then(|| (x as usize, y as usize).into())
+            }
+        }
+    }
+}
@@ -0,0 +1,1043 @@
+use std::{
+    io::{self, BufRead, BufReader, Lines, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    action::Action,
+    lobby::{self, Announcement},
+    map::Map,
+    replay::Replay,
+    rng::Rng,
+    wizard::Team,
+    Battle, Seat, SpellChoice, SpellSelect,
+};
+
+///bump this whenever `ClientMessage`, `ServerMessage` or `Announcement` change
+pub const NET_VERSION: u32 = 7;
+
+pub const DEFAULT_PORT: u16 = 4741;
+
+///how long a new connection gets to say hello before it's dropped
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+///how long a peer may take to accept a message before it's dropped, writes happen while the
+///battle is locked so one stuck peer would hold up everyone
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+//both directions are one json message per line
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    ///the first line a client sends, `game`, `seat` and the `token` it was welcomed with ask for
+    ///the same seat back after a dropped connection, spectators only get to watch `game`
+    Hello {
+        version: u32,
+        seat: Option<usize>,
+        game: Option<u64>,
+        token: Option<u64>,
+        spectate: bool,
+    },
+    ///`step` is how many actions the client had seen, so actions on a stale battle are refused
+    Action {
+        step: usize,
+        action: Action,
+    },
+    Undo,
+    ///asks for the whole battle again, e.g. after an action didn't apply locally
+    Resync,
+    ///picks the build of a seat before the battle starts, clients can only pick their own
+    Build(usize, SpellChoice),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    ///which wizard the client plays and in which game, followed by the room or the whole battle,
+    ///`token` proves it's the same client when it rejoins
+    Welcome {
+        seat: usize,
+        game: u64,
+        token: u64,
+    },
+    ///sent to spectators instead of `Welcome`, they see the battle `delay` turns late
+    Watching {
+        game: u64,
+        delay: usize,
+    },
+    ///everyone's builds and seats while waiting for the host to start
+    Room(SpellSelect),
+    ///the whole battle, sent after joining, after undos and when asked for
+    State(Replay),
+    ///an action everyone should apply, `step` is how many actions came before it
+    Applied {
+        step: usize,
+        action: Action,
+    },
+    Rejected(String),
+    Joined(usize),
+    Left(usize),
+}
+
+///what a client needs to get its seat back after a dropped connection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rejoin {
+    pub game: u64,
+    pub seat: usize,
+    pub token: u64,
+}
+
+///who sent a message to the server: the host itself or the client playing a seat
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Peer {
+    Local,
+    Remote(usize),
+}
+
+struct Connection {
+    id: u64,
+    stream: TcpStream,
+}
+
+enum Stage {
+    ///picking builds before the battle
+    Room(SpellSelect),
+    ///the authoritative battle, clients only ever get actions that applied to it
+    Battle(Box<Battle>),
+}
+
+struct Shared {
+    stage: Stage,
+    ///tells games on the same dedicated server apart
+    game: u64,
+    turn_started: Instant,
+    ///the connection playing each remote seat
+    clients: Vec<Option<Connection>>,
+    ///what a client has to show to take each seat back
+    tokens: Vec<u64>,
+    rng: Rng,
+    spectators: Vec<Connection>,
+    ///how many turns spectators are behind, so they can't tell players what's going on
+    spectator_delay: usize,
+    ///how many actions spectators were sent, `None` until they got the battle
+    shown: Option<usize>,
+    local: Sender<ServerMessage>,
+    next_id: u64,
+}
+
+impl Shared {
+    fn state(&self) -> ServerMessage {
+        match &self.stage {
+            Stage::Room(room) => ServerMessage::Room(room.clone()),
+            Stage::Battle(battle) => ServerMessage::State(battle.replay()),
+        }
+    }
+
+    ///what `peer` gets instead of `state`, nobody sees the tiles other teams picked until
+    ///everyone has
+    fn state_for(&self, peer: Peer) -> ServerMessage {
+        match &self.stage {
+            Stage::Battle(battle) => {
+                ServerMessage::State(battle.replay().seen_by(&self.teams_of(peer)))
+            }
+            Stage::Room(_) => self.state(),
+        }
+    }
+
+    ///the teams whose picks `peer` may see, the host's are the ones of the humans at its screen
+    fn teams_of(&self, peer: Peer) -> Vec<Team> {
+        let Stage::Battle(battle) = &self.stage else {
+            return vec![];
+        };
+        let setup = battle.setup();
+        match peer {
+            Peer::Local => (0..setup.seats.len())
+                .filter(|&seat| setup.seats[seat].is_human())
+                .map(|seat| setup.teams[seat])
+                .collect(),
+            Peer::Remote(seat) => setup.teams.get(seat).copied().into_iter().collect(),
+        }
+    }
+
+    ///what spectators get instead of `state`, the battle as it was `spectator_delay` turns ago
+    fn spectated(&self) -> ServerMessage {
+        match &self.stage {
+            Stage::Room(room) => ServerMessage::Room(room.clone()),
+            Stage::Battle(battle) => {
+                let mut replay = battle.replay().seen_by(&[]);
+                replay
+                    .actions
+                    .truncate(visible_step(&replay.actions, self.spectator_delay));
+                ServerMessage::State(replay)
+            }
+        }
+    }
+
+    fn is_remote(&self, seat: usize) -> bool {
+        let seats = match &self.stage {
+            Stage::Room(room) => &room.seats,
+            Stage::Battle(battle) => &battle.setup().seats,
+        };
+        seats.get(seat).is_some_and(|seat| seat.is_remote())
+    }
+
+    ///remote seats nobody is connected to
+    fn free_seats(&self) -> usize {
+        (0..self.clients.len())
+            .filter(|seat| self.is_remote(*seat) && self.clients[*seat].is_none())
+            .count()
+    }
+
+    fn send_to(&mut self, peer: Peer, message: &ServerMessage) {
+        match peer {
+            Peer::Local => {
+                let _ = self.local.send(message.clone());
+            }
+            Peer::Remote(seat) => {
+                if let Some(connection) = &mut self.clients[seat] {
+                    if write_line(&mut connection.stream, message).is_err() {
+                        //so its reading thread notices too
+                        let _ = connection.stream.shutdown(std::net::Shutdown::Both);
+                        self.clients[seat] = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn broadcast(&mut self, message: &ServerMessage) {
+        self.send_to(Peer::Local, message);
+        (0..self.clients.len()).for_each(|seat| self.send_to(Peer::Remote(seat), message));
+        match message {
+            ServerMessage::State(_) | ServerMessage::Applied { .. } => self.sync_spectators(),
+            _ => self.send_to_spectators(message),
+        }
+    }
+
+    ///sends everyone the whole battle, as far as each of them may see it
+    fn broadcast_state(&mut self) {
+        for peer in std::iter::once(Peer::Local).chain((0..self.clients.len()).map(Peer::Remote)) {
+            let state = self.state_for(peer);
+            self.send_to(peer, &state);
+        }
+        match self.stage {
+            Stage::Room(_) => {
+                let state = self.state();
+                self.send_to_spectators(&state);
+            }
+            Stage::Battle(_) => self.sync_spectators(),
+        }
+    }
+
+    ///tells everyone about an action that applied, a picked tile only reaches the picker's team,
+    ///the host included, and the last pick sends everyone the battle with all of them
+    fn announce(&mut self, step: usize, action: Action) {
+        let Stage::Battle(battle) = &self.stage else {
+            return;
+        };
+        if !matches!(action, Action::Place(_)) {
+            return self.broadcast(&ServerMessage::Applied { step, action });
+        }
+        if !battle.is_placing() {
+            return self.broadcast_state();
+        }
+        let replay = battle.replay();
+        let messages = std::iter::once(Peer::Local)
+            .chain((0..self.clients.len()).map(Peer::Remote))
+            .map(|peer| {
+                let seen = replay.seen_by(&self.teams_of(peer));
+                let message = match seen.actions.get(step) {
+                    Some(&action) => ServerMessage::Applied { step, action },
+                    None => ServerMessage::State(seen),
+                };
+                (peer, message)
+            })
+            .collect_vec();
+        for (peer, message) in &messages {
+            self.send_to(*peer, message);
+        }
+        self.sync_spectators();
+    }
+
+    fn send_to_spectators(&mut self, message: &ServerMessage) {
+        self.spectators.retain_mut(|spectator| {
+            let sent = write_line(&mut spectator.stream, message).is_ok();
+            if !sent {
+                let _ = spectator.stream.shutdown(std::net::Shutdown::Both);
+            }
+            sent
+        });
+    }
+
+    ///sends spectators whatever happened more than `spectator_delay` turns ago
+    fn sync_spectators(&mut self) {
+        let Stage::Battle(battle) = &self.stage else {
+            return;
+        };
+        let actions = battle.replay().seen_by(&[]).actions;
+        let visible = visible_step(&actions, self.spectator_delay);
+        let placers = battle.setup().placers().len();
+        //undone actions can't be taken back one by one, nor can swapped starting tiles
+        let stale =
+            |shown: usize| shown > visible || (0 < shown && shown < placers && placers <= visible);
+        let messages = match self.shown {
+            Some(shown) if !stale(shown) => (shown..visible)
+                .map(|step| ServerMessage::Applied {
+                    step,
+                    action: actions[step],
+                })
+                .collect(),
+            _ => vec![self.spectated()],
+        };
+        self.shown = Some(visible);
+        for message in &messages {
+            self.send_to_spectators(message);
+        }
+    }
+
+    fn may_act(&self, peer: Peer, seat: usize) -> bool {
+        match peer {
+            Peer::Local => !self.is_remote(seat),
+            Peer::Remote(remote) => remote == seat,
+        }
+    }
+
+    fn handle(&mut self, peer: Peer, message: ClientMessage) {
+        let result = match message {
+            ClientMessage::Hello { .. } => Err(rejected("already said hello")),
+            ClientMessage::Resync => Err(self.state_for(peer)),
+            ClientMessage::Build(seat, build) => self.pick_build(peer, seat, build),
+            ClientMessage::Action { step, action } => self.act(peer, step, action),
+            ClientMessage::Undo => self.undo(peer),
+        };
+        if let Err(reply) = result {
+            self.send_to(peer, &reply);
+        }
+    }
+
+    fn pick_build(
+        &mut self,
+        peer: Peer,
+        seat: usize,
+        build: SpellChoice,
+    ) -> Result<(), ServerMessage> {
+        if !self.may_act(peer, seat) {
+            return Err(rejected("that isn't your seat"));
+        }
+        let Stage::Room(room) = &mut self.stage else {
+            return Err(rejected("the battle already started"));
+        };
+        if seat >= room.players.len() || !build.is_valid() {
+            return Err(rejected("that isn't a build"));
+        }
+        room.players[seat] = build;
+        self.broadcast_state();
+        Ok(())
+    }
+
+    fn act(&mut self, peer: Peer, step: usize, action: Action) -> Result<(), ServerMessage> {
+        let Stage::Battle(battle) = &self.stage else {
+            return Err(rejected("the battle hasn't started"));
+        };
+        if !self.may_act(peer, battle.current_player()) {
+            return Err(rejected("it isn't your turn"));
+        }
+        //the client acted on an old battle, so it gets the current one
+        if step != battle.actions().len() {
+            return Err(self.state_for(peer));
+        }
+        let Stage::Battle(battle) = &mut self.stage else {
+            unreachable!();
+        };
+        battle.apply(action).map_err(|e| rejected(&e.to_string()))?;
+        if matches!(action, Action::EndTurn | Action::Place(_)) {
+            self.turn_started = Instant::now();
+        }
+        self.announce(step, action);
+        Ok(())
+    }
+
+    fn undo(&mut self, peer: Peer) -> Result<(), ServerMessage> {
+        let Stage::Battle(battle) = &self.stage else {
+            return Err(rejected("the battle hasn't started"));
+        };
+        if !self.may_act(peer, battle.current_player()) {
+            return Err(rejected("it isn't your turn"));
+        }
+        let Stage::Battle(battle) = &mut self.stage else {
+            unreachable!();
+        };
+        if battle.undo() {
+            self.broadcast_state();
+        }
+        Ok(())
+    }
+
+    ///the seat a new connection gets: the one it asked for if that one is remote and it has the
+    ///seat's token, otherwise the first free remote seat, an old connection on the asked for seat
+    ///is assumed to be dead
+    fn pick_seat(&self, asked: Option<usize>, token: Option<u64>) -> Result<usize, ServerMessage> {
+        match asked {
+            Some(seat) if self.is_remote(seat) => match token == Some(self.tokens[seat]) {
+                true => Ok(seat),
+                false => Err(rejected("that seat belongs to someone else")),
+            },
+            _ => (0..self.clients.len())
+                .find(|seat| self.is_remote(*seat) && self.clients[*seat].is_none())
+                .ok_or_else(|| rejected("no free seats")),
+        }
+    }
+}
+
+///how many of `actions` came before the start of the turn `delay` turns ago
+fn visible_step(actions: &[Action], delay: usize) -> usize {
+    if delay == 0 {
+        return actions.len();
+    }
+    let ends = actions
+        .iter()
+        .positions(|action| *action == Action::EndTurn)
+        .collect_vec();
+    match ends.len().checked_sub(delay) {
+        Some(turn) if turn > 0 => ends[turn - 1] + 1,
+        _ => 0,
+    }
+}
+
+///a thread that panicked while holding the lock shouldn't take the rest of the server down with
+///it, the timer included
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn rejected(reason: &str) -> ServerMessage {
+    ServerMessage::Rejected(reason.to_owned())
+}
+
+fn write_line<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let line = serde_json::to_string(message).map_err(io::Error::other)?;
+    writeln!(stream, "{line}")?;
+    stream.flush()
+}
+
+///hosts a battle: plays the seats that aren't `Seat::Remote` itself and lets clients connect
+///for the rest
+pub struct Server {
+    shared: Arc<Mutex<Shared>>,
+    local: Receiver<ServerMessage>,
+    ///0 when connections are handed over with `adopt` instead
+    port: u16,
+    closed: Arc<AtomicBool>,
+}
+
+impl Server {
+    ///hosts a battle that already started
+    pub fn host(port: u16, battle: Battle) -> io::Result<Self> {
+        Self::new(Stage::Battle(Box::new(battle)), 0).listen(port)
+    }
+
+    ///hosts a room where everyone picks their build until the host calls `start`
+    pub fn host_room(port: u16, room: SpellSelect) -> io::Result<Self> {
+        Self::new(Stage::Room(room), 0).listen(port)
+    }
+
+    ///a room that doesn't listen itself, connections are handed to it with `adopt`
+    pub fn room(room: SpellSelect, game: u64) -> Self {
+        Self::new(Stage::Room(room), game)
+    }
+
+    fn new(stage: Stage, game: u64) -> Self {
+        let seats = match &stage {
+            Stage::Room(room) => room.players.len(),
+            Stage::Battle(battle) => battle.wizards().len(),
+        };
+        let (sender, local) = mpsc::channel();
+        let mut rng = Rng::new(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_nanos() as u64)
+                .unwrap_or_default()
+                ^ game,
+        );
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                stage,
+                game,
+                turn_started: Instant::now(),
+                clients: (0..seats).map(|_| None).collect(),
+                tokens: (0..seats).map(|_| rng.next_u64()).collect(),
+                rng,
+                spectators: vec![],
+                spectator_delay: 0,
+                shown: None,
+                local: sender,
+                next_id: 0,
+            })),
+            local,
+            port: 0,
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn listen(mut self, port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        self.port = listener.local_addr()?.port();
+        let shared = self.shared.clone();
+        let closed = self.closed.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if closed.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let shared = shared.clone();
+                //a slow hello shouldn't hold up everyone else
+                thread::spawn(move || {
+                    if let Some(handshake) = Handshake::read(stream) {
+                        adopt(&shared, handshake);
+                    }
+                });
+            }
+        });
+        Ok(self)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn game(&self) -> u64 {
+        self.lock().game
+    }
+
+    ///gives a seat to a connection some other listener accepted
+    pub fn adopt(&self, handshake: Handshake) {
+        adopt(&self.shared, handshake);
+    }
+
+    ///how many turns behind the players spectators are, 0 to show them everything right away
+    pub fn set_spectator_delay(&self, turns: usize) {
+        let mut shared = self.lock();
+        shared.spectator_delay = turns;
+        //the battle they have may now be ahead of what they should see
+        shared.shown = None;
+        shared.sync_spectators();
+    }
+
+    pub fn spectators(&self) -> usize {
+        self.lock().spectators.len()
+    }
+
+    ///remote seats nobody is connected to
+    pub fn free_seats(&self) -> usize {
+        self.lock().free_seats()
+    }
+
+    ///ends the turn of anyone who takes longer than `limit`
+    pub fn set_turn_time(&self, limit: Duration) {
+        let shared = self.shared.clone();
+        let closed = self.closed.clone();
+        thread::spawn(move || {
+            while !closed.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(100));
+                let mut shared = lock(&shared);
+                if shared.turn_started.elapsed() < limit {
+                    continue;
+                }
+                let Stage::Battle(battle) = &mut shared.stage else {
+                    continue;
+                };
+                if battle.is_over() {
+                    continue;
+                }
+                let step = battle.actions().len();
+                let action = battle.pass_action();
+                battle.apply(action).expect("passing is always legal");
+                shared.turn_started = Instant::now();
+                shared.announce(step, action);
+            }
+        });
+    }
+
+    ///acts for whichever local seat is up
+    pub fn send(&self, message: ClientMessage) {
+        self.lock().handle(Peer::Local, message);
+    }
+
+    ///what the server told the host, the same things clients are told
+    pub fn try_recv(&self) -> Option<ServerMessage> {
+        self.local.try_recv().ok()
+    }
+
+    ///how many actions the battle has taken, once it started, cheaper than cloning it to look
+    pub fn steps(&self) -> Option<usize> {
+        match &self.lock().stage {
+            Stage::Room(_) => None,
+            Stage::Battle(battle) => Some(battle.actions().len()),
+        }
+    }
+
+    ///the battle, once it started
+    pub fn battle(&self) -> Option<Battle> {
+        match &self.lock().stage {
+            Stage::Room(_) => None,
+            Stage::Battle(battle) => Some(*battle.clone()),
+        }
+    }
+
+    ///changes the room, except for the builds clients picked for themselves
+    pub fn set_room(&self, mut room: SpellSelect) {
+        let mut shared = self.lock();
+        let Stage::Room(current) = &shared.stage else {
+            return;
+        };
+        for seat in 0..room.players.len().min(current.players.len()) {
+            if current.seats[seat].is_remote() && room.seats[seat].is_remote() {
+                room.players[seat] = current.players[seat].clone();
+            }
+        }
+        //clients of seats that aren't remote anymore have to go
+        for seat in 0..shared.clients.len() {
+            if room.seats.get(seat).is_none_or(|seat| !seat.is_remote()) {
+                if let Some(old) = shared.clients[seat].take() {
+                    let _ = old.stream.shutdown(std::net::Shutdown::Both);
+                }
+            }
+        }
+        shared.clients.resize_with(room.players.len(), || None);
+        let Shared { tokens, rng, .. } = &mut *shared;
+        tokens.resize_with(room.players.len(), || rng.next_u64());
+        shared.stage = Stage::Room(room);
+        shared.broadcast_state();
+    }
+
+    ///starts the battle once every remote seat is taken and every build is complete
+    pub fn start(&self) -> Result<(), String> {
+        let mut shared = self.lock();
+        let Stage::Room(room) = &shared.stage else {
+            return Err("the battle already started".to_owned());
+        };
+        if !room.is_playable() {
+            return Err("everyone is on the same team".to_owned());
+        }
+        if shared.free_seats() > 0 {
+            return Err("waiting for players to join".to_owned());
+        }
+        if room.players.iter().any(|build| build.unused > 0) {
+            return Err("not everyone finished their build".to_owned());
+        }
+        shared.stage = Stage::Battle(Box::new(Battle::new(room)));
+        shared.turn_started = Instant::now();
+        shared.broadcast_state();
+        Ok(())
+    }
+
+    ///broadcasts the game on the local network every second until the server is dropped
+    pub fn announce(&self, name: String) -> io::Result<()> {
+        self.announce_to(name, lobby::DISCOVERY_PORT)
+    }
+
+    ///announces to lobbies listening on another port
+    pub fn announce_to(&self, name: String, discovery_port: u16) -> io::Result<()> {
+        let socket = lobby::announce_socket()?;
+        let shared = self.shared.clone();
+        let closed = self.closed.clone();
+        let port = self.port();
+        thread::spawn(move || {
+            while !closed.load(Ordering::Relaxed) {
+                let announcement = {
+                    let shared = lock(&shared);
+                    Announcement {
+                        version: NET_VERSION,
+                        name: name.clone(),
+                        port,
+                        map: match &shared.stage {
+                            Stage::Room(_) => Map::default(),
+                            Stage::Battle(battle) => battle.setup().map,
+                        },
+                        free_seats: shared.free_seats(),
+                        started: matches!(shared.stage, Stage::Battle(_)),
+                    }
+                };
+                //a missed announcement is fine, the next one comes soon
+                let _ = lobby::send_announcement(&socket, &announcement, discovery_port);
+                thread::sleep(lobby::ANNOUNCE_INTERVAL);
+            }
+        });
+        Ok(())
+    }
+
+    ///which seats have a client connected
+    pub fn connected(&self) -> Vec<bool> {
+        self.lock().clients.iter().map(Option::is_some).collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        lock(&self.shared)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+        if self.port != 0 {
+            //wakes the accept loop up so it notices
+            let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+        }
+        let shared = self.lock();
+        for connection in shared.clients.iter().flatten().chain(&shared.spectators) {
+            let _ = connection.stream.shutdown(std::net::Shutdown::Both);
+        }
+    }
+}
+
+///a connection that said hello, before it's given a seat
+pub struct Handshake {
+    stream: TcpStream,
+    lines: Lines<BufReader<TcpStream>>,
+    pub seat: Option<usize>,
+    pub game: Option<u64>,
+    pub token: Option<u64>,
+    pub spectate: bool,
+}
+
+impl Handshake {
+    ///reads the hello, turning the client away if there isn't a proper one
+    pub fn read(stream: TcpStream) -> Option<Self> {
+        stream.set_read_timeout(Some(HELLO_TIMEOUT)).ok()?;
+        let mut lines = BufReader::new(stream.try_clone().ok()?).lines();
+        let hello = lines
+            .next()
+            .and_then(|line| serde_json::from_str(&line.ok()?).ok());
+        //once seated a client may take as long as it likes between messages
+        stream.set_read_timeout(None).ok()?;
+        let handshake = |seat, game, token, spectate| Handshake {
+            stream,
+            lines,
+            seat,
+            game,
+            token,
+            spectate,
+        };
+        match hello {
+            Some(ClientMessage::Hello {
+                version,
+                seat,
+                game,
+                token,
+                spectate,
+            }) if version == NET_VERSION => Some(handshake(seat, game, token, spectate)),
+            Some(ClientMessage::Hello { version, .. }) => {
+                handshake(None, None, None, false).reject(&format!(
+                    "client speaks version {version}, the host speaks {NET_VERSION}"
+                ));
+                None
+            }
+            _ => {
+                handshake(None, None, None, false).reject("expected a hello");
+                None
+            }
+        }
+    }
+
+    pub fn reject(mut self, reason: &str) {
+        let _ = write_line(&mut self.stream, &rejected(reason));
+    }
+}
+
+///seats the client right away, so the seat is taken by the time this returns
+fn adopt(shared: &Arc<Mutex<Shared>>, handshake: Handshake) {
+    if handshake.spectate {
+        return watch(shared, handshake);
+    }
+    let Handshake {
+        stream,
+        lines,
+        seat: asked,
+        game,
+        token,
+        ..
+    } = handshake;
+    let (seat, id) = {
+        let mut shared = lock(shared);
+        //a seat from some other game isn't this game's seat
+        let asked = asked.filter(|_| game == Some(shared.game));
+        let seat = match shared.pick_seat(asked, token) {
+            Ok(seat) => seat,
+            Err(reply) => {
+                drop(shared);
+                let _ = write_line(&mut { stream }, &reply);
+                return;
+            }
+        };
+        let Ok(writer) = stream.try_clone() else {
+            return;
+        };
+        if writer.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+            return;
+        }
+        if let Some(old) = shared.clients[seat].take() {
+            let _ = old.stream.shutdown(std::net::Shutdown::Both);
+        }
+        //a new player gets a new token, so whoever had the seat before can't take it back
+        if asked != Some(seat) {
+            shared.tokens[seat] = shared.rng.next_u64();
+        }
+        let id = shared.next_id;
+        shared.next_id += 1;
+        shared.clients[seat] = Some(Connection { id, stream: writer });
+        let state = shared.state_for(Peer::Remote(seat));
+        let welcome = ServerMessage::Welcome {
+            seat,
+            game: shared.game,
+            token: shared.tokens[seat],
+        };
+        shared.send_to(Peer::Remote(seat), &welcome);
+        shared.send_to(Peer::Remote(seat), &state);
+        shared.broadcast(&ServerMessage::Joined(seat));
+        (seat, id)
+    };
+    let shared = shared.clone();
+    thread::spawn(move || serve(&shared, seat, id, lines));
+}
+
+fn watch(shared: &Arc<Mutex<Shared>>, handshake: Handshake) {
+    let Ok(writer) = handshake.stream.try_clone() else {
+        return;
+    };
+    if writer.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+        return;
+    }
+    let id = {
+        let mut shared = lock(shared);
+        let id = shared.next_id;
+        shared.next_id += 1;
+        let mut spectator = Connection { id, stream: writer };
+        let watching = ServerMessage::Watching {
+            game: shared.game,
+            delay: shared.spectator_delay,
+        };
+        let state = shared.spectated();
+        if write_line(&mut spectator.stream, &watching).is_err()
+            || write_line(&mut spectator.stream, &state).is_err()
+        {
+            return;
+        }
+        shared.spectators.push(spectator);
+        id
+    };
+    let shared = shared.clone();
+    thread::spawn(move || {
+        for line in handshake.lines {
+            let Ok(line) = line else {
+                break;
+            };
+            let mut shared = lock(&shared);
+            let reply = match serde_json::from_str(&line) {
+                Ok(ClientMessage::Resync) => shared.spectated(),
+                _ => rejected("spectators can only watch"),
+            };
+            if let Some(spectator) = shared.spectators.iter_mut().find(|s| s.id == id) {
+                let _ = write_line(&mut spectator.stream, &reply);
+            }
+        }
+        lock(&shared)
+            .spectators
+            .retain(|spectator| spectator.id != id);
+    });
+}
+
+///talks to one seated client until it disconnects
+fn serve(shared: &Mutex<Shared>, seat: usize, id: u64, lines: Lines<BufReader<TcpStream>>) {
+    for line in lines {
+        let Ok(line) = line else {
+            break;
+        };
+        let mut shared = lock(shared);
+        match serde_json::from_str(&line) {
+            Ok(message) => shared.handle(Peer::Remote(seat), message),
+            Err(e) => shared.send_to(
+                Peer::Remote(seat),
+                &ServerMessage::Rejected(format!("couldn't read `{line}`: {e}")),
+            ),
+        }
+    }
+
+    let mut shared = lock(shared);
+    //a reconnect may already have taken the seat over
+    if shared.clients[seat].as_ref().is_some_and(|c| c.id == id) {
+        shared.clients[seat] = None;
+        shared.broadcast(&ServerMessage::Left(seat));
+    }
+}
+
+///a connection to a host, reading happens on its own thread
+pub struct Client {
+    stream: TcpStream,
+    messages: Receiver<ServerMessage>,
+    seat: Option<usize>,
+    game: Option<u64>,
+    token: Option<u64>,
+    spectating: bool,
+}
+
+impl Client {
+    ///`rejoin` is the seat to get back after a dropped connection
+    pub fn connect(addr: impl ToSocketAddrs, rejoin: Option<Rejoin>) -> io::Result<Self> {
+        Self::open(
+            addr,
+            ClientMessage::Hello {
+                version: NET_VERSION,
+                seat: rejoin.map(|rejoin| rejoin.seat),
+                game: rejoin.map(|rejoin| rejoin.game),
+                token: rejoin.map(|rejoin| rejoin.token),
+                spectate: false,
+            },
+        )
+    }
+
+    ///joins without a seat, `game` picks a match on a dedicated server
+    pub fn watch(addr: impl ToSocketAddrs, game: Option<u64>) -> io::Result<Self> {
+        Self::open(
+            addr,
+            ClientMessage::Hello {
+                version: NET_VERSION,
+                seat: None,
+                game,
+                token: None,
+                spectate: true,
+            },
+        )
+    }
+
+    fn open(addr: impl ToSocketAddrs, hello: ClientMessage) -> io::Result<Self> {
+        let spectating = matches!(hello, ClientMessage::Hello { spectate: true, .. });
+        let mut stream = TcpStream::connect(addr)?;
+        let reader = stream.try_clone()?;
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(message) = line.map(|line| serde_json::from_str(&line)) else {
+                    break;
+                };
+                let Ok(message) = message else {
+                    continue;
+                };
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        write_line(&mut stream, &hello)?;
+        Ok(Self {
+            stream,
+            messages,
+            seat: None,
+            game: None,
+            token: None,
+            spectating,
+        })
+    }
+
+    ///the seat the host gave us, once it did
+    pub fn seat(&self) -> Option<usize> {
+        self.seat
+    }
+
+    pub fn game(&self) -> Option<u64> {
+        self.game
+    }
+
+    ///what it takes to get the seat back, once the host gave us one
+    pub fn rejoin(&self) -> Option<Rejoin> {
+        Some(Rejoin {
+            game: self.game?,
+            seat: self.seat?,
+            token: self.token?,
+        })
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.spectating
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        write_line(&mut self.stream, message)
+    }
+
+    ///`Err(Disconnected)` once the host is gone
+    pub fn try_recv(&mut self) -> Result<ServerMessage, TryRecvError> {
+        let message = self.messages.try_recv()?;
+        self.note(&message);
+        Ok(message)
+    }
+
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<ServerMessage, RecvTimeoutError> {
+        let message = self.messages.recv_timeout(timeout)?;
+        self.note(&message);
+        Ok(message)
+    }
+
+    fn note(&mut self, message: &ServerMessage) {
+        match message {
+            ServerMessage::Welcome { seat, game, token } => {
+                self.seat = Some(*seat);
+                self.game = Some(*game);
+                self.token = Some(*token);
+            }
+            ServerMessage::Watching { game, .. } => self.game = Some(*game),
+            _ => {}
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(std::net::Shutdown::Both);
+    }
+}
+
+///either end of a networked battle
+pub enum Session {
+    Host(Server),
+    Join(Client),
+}
+
+impl Session {
+    ///whether this end decides for `player`, hosts play every seat that isn't remote
+    pub fn plays(&self, seats: &[Seat], player: usize) -> bool {
+        match self {
+            Session::Host(_) => seats.get(player).is_none_or(|seat| !seat.is_remote()),
+            Session::Join(client) => client.seat() == Some(player),
+        }
+    }
+
+    pub fn send(&mut self, message: ClientMessage) -> io::Result<()> {
+        match self {
+            Session::Host(server) => {
+                server.send(message);
+                Ok(())
+            }
+            Session::Join(client) => client.send(&message),
+        }
+    }
+
+    ///`Err(Disconnected)` once a client lost its host
+    pub fn try_recv(&mut self) -> Result<ServerMessage, TryRecvError> {
+        match self {
+            Session::Host(server) => server.try_recv().ok_or(TryRecvError::Empty),
+            Session::Join(client) => client.try_recv(),
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use std::ops::{Add, Sub};
 
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use super::board::{HEIGHT, WIDTH};
 
-#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub struct Position {
     pub x: usize,
     pub y: usize,
@@ -76,6 +77,18 @@ impl Position {
         }
     }
 
+    ///the way towards `other` along the axis it's further away on, none if it's this tile
+    pub fn direction_to(self, other: Position) -> Option<Direction> {
+        let (x, y) = self.signed_dist(other);
+        match x.abs() >= y.abs() {
+            _ if (x, y) == (0, 0) => None,
+            true if x > 0 => Some(Direction::Right),
+            true => Some(Direction::Left),
+            false if y > 0 => Some(Direction::Down),
+            false => Some(Direction::Up),
+        }
+    }
+
     pub fn dist(self, other: Position) -> Position {
         (
             (self.x as isize - other.x as isize)
@@ -106,7 +119,18 @@ impl Position {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    EnumIter,
+    strum_macros::EnumCount,
+    strum_macros::FromRepr,
+    Serialize,
+    Deserialize,
+)]
 pub enum Direction {
     Up,
     Down,
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    position::{Direction, Position},
+    wizard::{Team, Wizard},
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Projectile {
+    pub position: Position,
+    pub projectile_type: ProjectileType,
+    pub damage: usize,
+    pub direction: Direction,
+    pub owner: Team,
+    ///the wizard who conjured it, it moves at the end of their turns
+    pub caster: usize,
+    pub guiding: bool,
+    pub speed: usize,
+    pub passable: bool,
+    pub lifetime: usize,
+}
+
+impl Projectile {
+    ///projectiles don't hurt their owner's team
+    pub fn hurts(&self, wizard: &Wizard) -> bool {
+        self.owner != wizard.team
+    }
+
+    pub fn take_damage(&mut self, damage: usize) {
+        self.damage = self.damage.saturating_sub(damage * 2);
+    }
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumCount,
+    Serialize,
+    Deserialize,
+)]
+pub enum ProjectileType {
+    Fireball,
+    Spike,
+    Boulder,
+    Wall,
+    WindBolt,
+}
@@ -0,0 +1,183 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{action::Action, save::SaveError, wizard::Team, Battle, MatchSetup};
+
+///bump this whenever the layout of a `Replay` changes
+pub const REPLAY_VERSION: u32 = 4;
+
+pub const REPLAY_PATH: &str = "wizard-fight.replay.json";
+
+///a whole match: how it started and every action taken since, which is enough to re-simulate it
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub setup: MatchSetup,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplayFile {
+    version: u32,
+    replay: Replay,
+}
+
+impl Battle {
+    pub fn replay(&self) -> Replay {
+        Replay {
+            setup: self.setup.clone(),
+            actions: self.actions.clone(),
+        }
+    }
+
+    ///the battle as `teams` get to see it, see `Replay::seen_by`
+    pub fn seen_by(&self, teams: &[Team]) -> Battle {
+        if !self.is_placing() {
+            return self.clone();
+        }
+        let replay = self.replay().seen_by(teams);
+        replay
+            .battle_at(replay.actions.len())
+            .expect("seen_by only keeps actions that applied")
+    }
+}
+
+impl Replay {
+    ///the battle after the first `step` actions, replays from files or hosts may not add up
+    pub fn battle_at(&self, step: usize) -> Result<Battle, SaveError> {
+        self.setup.check()?;
+        let mut battle = Battle::from_setup(self.setup.clone());
+        self.actions[..step.min(self.actions.len())]
+            .iter()
+            .try_for_each(|action| battle.apply(*action))
+            .map_err(|e| SaveError::Inconsistent(e.to_string()))?;
+        Ok(battle)
+    }
+
+    ///the replay as `teams` get to see it: until everyone picked a starting tile, the tiles other
+    ///teams picked are swapped for the ones passing would have picked, no teams see nobody's
+    pub fn seen_by(&self, teams: &[Team]) -> Replay {
+        let placers = self.setup.placers();
+        //the last pick reveals them all anyway
+        if self.actions.len() >= placers.len() {
+            return self.clone();
+        }
+        let mut battle = Battle::from_setup(self.setup.clone());
+        let mut actions = vec![];
+        for (&action, &wizard) in self.actions.iter().zip(&placers) {
+            let shown = match teams.contains(&self.setup.teams[wizard]) {
+                true => action,
+                false => battle.pass_action(),
+            };
+            //a tile of theirs may be taken by a swapped one if spawn zones overlap
+            if battle.apply(shown).is_err() {
+                break;
+            }
+            actions.push(shown);
+        }
+        Replay {
+            setup: self.setup.clone(),
+            actions,
+        }
+    }
+
+    pub fn turn_count(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|action| **action == Action::EndTurn)
+            .count()
+    }
+
+    ///the step at which `turn` begins
+    pub fn step_of_turn(&self, turn: usize) -> usize {
+        if turn == 0 {
+            return 0;
+        }
+        self.actions
+            .iter()
+            .enumerate()
+            .filter(|(_, action)| **action == Action::EndTurn)
+            .nth(turn - 1)
+            .map_or(self.actions.len(), |(i, _)| i + 1)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let file = ReplayFile {
+            version: REPLAY_VERSION,
+            replay: self.clone(),
+        };
+        Ok(fs::write(path, serde_json::to_string_pretty(&file)?)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let file: ReplayFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if file.version != REPLAY_VERSION {
+            return Err(SaveError::Version(file.version));
+        }
+        //make sure every recorded action actually happened as recorded
+        file.replay.battle_at(file.replay.actions.len())?;
+        Ok(file.replay)
+    }
+}
+
+///read-only playback of a replay
+pub struct ReplayViewer {
+    replay: Replay,
+    step: usize,
+    battle: Battle,
+}
+
+impl ReplayViewer {
+    ///checks the replay all the way through, so stepping through it later can't fail
+    pub fn new(replay: Replay) -> Result<Self, SaveError> {
+        replay.battle_at(replay.actions.len())?;
+        let battle = replay.battle_at(0)?;
+        Ok(Self {
+            replay,
+            step: 0,
+            battle,
+        })
+    }
+
+    pub fn battle(&self) -> &Battle {
+        &self.battle
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.replay.actions.len()
+    }
+
+    pub fn step_forward(&mut self) {
+        if let Some(action) = self.replay.actions.get(self.step) {
+            self.battle
+                .apply(*action)
+                .expect("replays are checked when the viewer is made");
+            self.step += 1;
+        }
+    }
+
+    ///battles can't be rewound, so this re-simulates from the start
+    pub fn step_back(&mut self) {
+        self.go_to_step(self.step.saturating_sub(1));
+    }
+
+    pub fn jump_to_turn(&mut self, turn: usize) {
+        self.go_to_step(self.replay.step_of_turn(turn));
+    }
+
+    fn go_to_step(&mut self, step: usize) {
+        self.battle = self
+            .replay
+            .battle_at(step)
+            .expect("replays are checked when the viewer is made");
+        self.step = step.min(self.replay.actions.len());
+    }
+}
@@ -0,0 +1,264 @@
+use std::{collections::HashSet, fmt::Display, fs, path::Path};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    board::Board, spell::Spell, Battle, MatchSetup, Model, SpellChoice, SpellSelect, MAX_MANA,
+};
+
+///bump this whenever the layout of a saved `Battle` or `SpellSelect` changes
+pub const SAVE_VERSION: u32 = 9;
+
+pub const SAVE_PATH: &str = "wizard-fight.save.json";
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile<T> {
+    version: u32,
+    state: T,
+}
+
+#[derive(Deserialize)]
+enum SavedModel {
+    Battle(Box<Battle>),
+    SpellSelect(SpellSelect),
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Format(serde_json::Error),
+    Version(u32),
+    Inconsistent(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "couldn't access save file: {e}"),
+            SaveError::Format(e) => write!(f, "save file is malformed: {e}"),
+            SaveError::Version(v) => write!(
+                f,
+                "save file has version {v}, but only version {SAVE_VERSION} is supported"
+            ),
+            SaveError::Inconsistent(reason) => write!(f, "save file is inconsistent: {reason}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Format(value)
+    }
+}
+
+impl Model {
+    pub fn to_save_string(&self) -> Result<String, SaveError> {
+        #[derive(Serialize)]
+        enum SavedModelRef<'a> {
+            Battle(&'a Battle),
+            SpellSelect(&'a SpellSelect),
+        }
+        let state = match self {
+            Model::Battle(battle) => SavedModelRef::Battle(battle),
+            Model::SpellSelect(spell_select) => SavedModelRef::SpellSelect(spell_select),
+            //lets a game be picked back up from any point in a replay
+            Model::Replay(viewer) => SavedModelRef::Battle(viewer.battle()),
+        };
+        Ok(serde_json::to_string_pretty(&SaveFile {
+            version: SAVE_VERSION,
+            state,
+        })?)
+    }
+
+    pub fn from_save_str(save: &str) -> Result<Self, SaveError> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+        let Version { version } = serde_json::from_str(save)?;
+        if version != SAVE_VERSION {
+            return Err(SaveError::Version(version));
+        }
+        let save: SaveFile<SavedModel> = serde_json::from_str(save)?;
+        Ok(match save.state {
+            SavedModel::Battle(mut battle) => {
+                battle.rebuild()?;
+                Model::Battle(battle)
+            }
+            SavedModel::SpellSelect(spell_select) => {
+                spell_select.check()?;
+                Model::SpellSelect(spell_select)
+            }
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        Ok(fs::write(path, self.to_save_string()?)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        Self::from_save_str(&fs::read_to_string(path)?)
+    }
+}
+
+fn inconsistent<T>(reason: String) -> Result<T, SaveError> {
+    Err(SaveError::Inconsistent(reason))
+}
+
+impl SpellChoice {
+    fn check(&self) -> Result<(), SaveError> {
+        let points = [self.water, self.fire, self.earth, self.wind];
+        if points.iter().any(|&p| p > 4) {
+            return inconsistent("an element has more than 4 points".to_owned());
+        }
+        if points.iter().sum::<usize>() + self.unused != SpellChoice::default().unused {
+            return inconsistent("spell points don't add up".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl SpellSelect {
+    fn check(&self) -> Result<(), SaveError> {
+        if !self.is_playable() {
+            return inconsistent("players, seats and teams don't add up to a match".to_owned());
+        }
+        self.players.iter().try_for_each(SpellChoice::check)
+    }
+}
+
+impl MatchSetup {
+    ///the checks the room it came from passed, so a battle can be set up from it
+    pub(super) fn check(&self) -> Result<(), SaveError> {
+        SpellSelect {
+            players: self.players.clone(),
+            seats: self.seats.clone(),
+            teams: self.teams.clone(),
+            placement: self.placement,
+        }
+        .check()
+    }
+}
+
+impl Battle {
+    ///rebuilds everything that isn't saved and makes sure the saved lists agree with each other
+    fn rebuild(&mut self) -> Result<(), SaveError> {
+        self.setup.check()?;
+        if self.wizards.is_empty() {
+            return inconsistent("there are no wizards".to_owned());
+        }
+        if self.setup.teams.len() != self.wizards.len()
+            || self
+                .wizards
+                .iter()
+                .zip(&self.setup.teams)
+                .any(|(wiz, team)| wiz.team != *team)
+        {
+            return inconsistent("wizards aren't on the teams they started on".to_owned());
+        }
+        if self.current_player >= self.wizards.len() {
+            return inconsistent(format!(
+                "current player {} doesn't exist",
+                self.current_player
+            ));
+        }
+        if self
+            .log
+            .iter()
+            .any(|entry| entry.actor >= self.wizards.len())
+        {
+            return inconsistent("log mentions a wizard that doesn't exist".to_owned());
+        }
+        if !self.schedule.fits(self.wizards.len()) {
+            return inconsistent("the turn schedule doesn't match the wizards".to_owned());
+        }
+        if !self.placements.is_empty() && self.placements.len() != self.wizards.len() {
+            return inconsistent("not every wizard has a starting tile to pick".to_owned());
+        }
+        let mut picked = HashSet::new();
+        for (i, tile) in self.placements.iter().enumerate() {
+            let Some(tile) = *tile else { continue };
+            if !Board::is_in_bounds(tile) {
+                return inconsistent(format!("wizard {i} picked a starting tile off the board"));
+            }
+            if !picked.insert(tile) {
+                return inconsistent(format!(
+                    "wizard {i} picked a starting tile someone else has"
+                ));
+            }
+        }
+
+        let mut occupied = HashSet::new();
+        for (i, wiz) in self.wizards.iter().enumerate() {
+            if !Board::is_in_bounds(wiz.position) {
+                return inconsistent(format!("wizard {i} is off the board"));
+            }
+            if !occupied.insert(wiz.position) {
+                return inconsistent(format!("wizard {i} shares a tile"));
+            }
+            if wiz.mana > MAX_MANA {
+                return inconsistent(format!("wizard {i} has more than {MAX_MANA} mana"));
+            }
+            if wiz.health > 100 {
+                return inconsistent(format!("wizard {i} has more than 100 health"));
+            }
+            if !is_legal_spell_list(&wiz.spells) {
+                return inconsistent(format!("wizard {i} has an invalid spell list"));
+            }
+            if wiz.effects.iter().any(|e| e.source >= self.wizards.len()) {
+                return inconsistent(format!("wizard {i} has an effect from nobody"));
+            }
+            if wiz.effects.iter().any(|e| e.turns == 0) {
+                return inconsistent(format!("wizard {i} has an effect that already wore off"));
+            }
+            if !wiz.effects.iter().map(|e| e.kind).all_unique() {
+                return inconsistent(format!("wizard {i} has the same effect twice"));
+            }
+        }
+        for (i, proj) in self.projectiles.iter().enumerate() {
+            if !Board::is_in_bounds(proj.position) {
+                return inconsistent(format!("projectile {i} is off the board"));
+            }
+            if !occupied.insert(proj.position) {
+                return inconsistent(format!("projectile {i} shares a tile"));
+            }
+            if self
+                .wizards
+                .get(proj.caster)
+                .is_none_or(|wiz| wiz.team != proj.owner)
+            {
+                return inconsistent(format!("projectile {i} wasn't cast by its team"));
+            }
+        }
+
+        //everything else has to be where the actions lead from the setup
+        let replayed = self.replay().battle_at(self.actions.len())?;
+        if serde_json::to_value(&replayed)? != serde_json::to_value(&*self)? {
+            return inconsistent("the state isn't what its actions lead to".to_owned());
+        }
+        self.board = Board::new(&self.wizards, &self.projectiles);
+        self.update_valid_move_positons();
+        Ok(())
+    }
+}
+
+///spells have to be in order and each one needs every lower spell of its element
+fn is_legal_spell_list(spells: &[Spell]) -> bool {
+    spells.len() <= SpellChoice::default().unused
+        && spells.windows(2).all(|s| (s[0] as usize) < (s[1] as usize))
+        && spells.iter().enumerate().all(|(i, spell)| {
+            let (element, points) = spell.requirement();
+            spells[..i]
+                .iter()
+                .filter(|other| other.requirement().0 == element)
+                .count()
+                == points - 1
+        })
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+///hands out turns: the turn order goes round and round, with room for skipped, delayed and extra
+///turns on top of it. who sits turns out isn't kept here, that's up to the wizards' effects
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Scheduler {
+    order: Vec<usize>,
+    ///index into `order` of whoever comes up next in the rotation
+    next: usize,
+    ///extra turns, taken before the rotation carries on
+    inserted: VecDeque<usize>,
+    ///turns in the rotation each wizard passes up because their turn was delayed
+    skips: Vec<usize>,
+    ///turns still to be taken before a delayed wizard gets their turn back, and whose it is
+    delayed: Vec<(usize, usize)>,
+}
+
+impl Scheduler {
+    pub fn new(order: Vec<usize>) -> Self {
+        Self {
+            skips: vec![0; order.len()],
+            order,
+            ..Default::default()
+        }
+    }
+
+    ///the next wizard `alive` says can act and the wizards that had to sit out on the way, none if
+    ///nobody is alive. `sit_out` is how many turns each wizard has to sit out, whatever they sit out
+    ///on the way is taken off it
+    pub fn next_turn(
+        &mut self,
+        alive: impl Fn(usize) -> bool,
+        sit_out: &mut [usize],
+    ) -> Option<(usize, Vec<usize>)> {
+        if !self.order.iter().any(|&wizard| alive(wizard)) {
+            return None;
+        }
+        let mut skipped = vec![];
+        loop {
+            let wizard = match self.inserted.pop_front() {
+                Some(wizard) => wizard,
+                None => {
+                    let wizard = self.order[self.next];
+                    self.next = (self.next + 1) % self.order.len();
+                    wizard
+                }
+            };
+            if !alive(wizard) {
+                continue;
+            }
+            if self.skips[wizard] > 0 {
+                self.skips[wizard] -= 1;
+                skipped.push(wizard);
+                continue;
+            }
+            if sit_out[wizard] > 0 {
+                sit_out[wizard] -= 1;
+                skipped.push(wizard);
+                continue;
+            }
+            //a turn went by, so delayed wizards get closer to theirs
+            for (wait, delayed) in &mut self.delayed {
+                *wait -= 1;
+                if *wait == 0 {
+                    self.inserted.push_back(*delayed);
+                }
+            }
+            self.delayed.retain(|(wait, _)| *wait > 0);
+            return Some((wizard, skipped));
+        }
+    }
+
+    ///who acts in the next `count` turns if nothing changes
+    pub fn upcoming(
+        &self,
+        count: usize,
+        alive: impl Fn(usize) -> bool,
+        mut sit_out: Vec<usize>,
+    ) -> Vec<usize> {
+        let mut schedule = self.clone();
+        (0..count)
+            .map_while(|_| {
+                schedule
+                    .next_turn(&alive, &mut sit_out)
+                    .map(|(wizard, _)| wizard)
+            })
+            .collect()
+    }
+
+    ///pushes the wizard's next turn back until `turns` other turns have been taken
+    pub fn delay(&mut self, wizard: usize, turns: usize) {
+        if turns == 0 {
+            return;
+        }
+        self.skips[wizard] += 1;
+        self.delayed.push((turns, wizard));
+    }
+
+    ///gives the wizard an extra turn right after the current one
+    pub fn insert(&mut self, wizard: usize) {
+        self.inserted.push_front(wizard);
+    }
+
+    ///whether this could be the schedule of a battle with this many wizards
+    pub fn fits(&self, wizards: usize) -> bool {
+        self.order.len() == wizards
+            && self.skips.len() == wizards
+            && self.next < wizards
+            && self
+                .order
+                .iter()
+                .chain(&self.inserted)
+                .chain(self.delayed.iter().map(|(_, wizard)| wizard))
+                .all(|&wizard| wizard < wizards)
+    }
+}
@@ -0,0 +1,349 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumIs, EnumIter};
+
+use strum::IntoEnumIterator;
+
+use super::{
+    action::SpellTarget,
+    board::{Entity, HEIGHT, WIDTH},
+    position::{Direction, Position},
+    projectile::{Projectile, ProjectileType},
+    wizard::Team,
+    Battle,
+};
+
+#[derive(
+    strum_macros::EnumIter,
+    strum_macros::EnumCount,
+    strum_macros::FromRepr,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub enum Spell {
+    //water
+    IncreasedCirculation = 0,
+    WaterSpear,
+    ManaDrain,
+    Stagnation,
+
+    //fire
+    Flame,
+    Fireball,
+    Explosion,
+    AuraOfFire,
+
+    //earth
+    StoneSkin,
+    Spikes,
+    Boulder,
+    Wall,
+
+    //wind
+    WindBolt,
+    Glide,
+    RepulsiveBlast,
+    Tornado,
+}
+
+impl Spell {
+    pub fn requirement(self) -> (SpellElement, usize) {
+        let points = self as usize % 4;
+        let element = match self as usize / 4 {
+            0 => SpellElement::Water,
+            1 => SpellElement::Fire,
+            2 => SpellElement::Earth,
+            3 => SpellElement::Wind,
+            _ => unreachable!(),
+        };
+        (element, points + 1)
+    }
+
+    pub fn mana_cost(self) -> usize {
+        match self {
+            Spell::IncreasedCirculation => 20,
+            Spell::WaterSpear => 30,
+            Spell::ManaDrain => 20,
+            Spell::Stagnation => 60,
+            Spell::Flame => 15,
+            Spell::Fireball => 30,
+            Spell::Explosion => 50,
+            Spell::AuraOfFire => 60,
+            Spell::StoneSkin => 25,
+            Spell::Spikes => 30,
+            Spell::Boulder => 50,
+            Spell::Wall => 70,
+            Spell::WindBolt => 10,
+            Spell::Glide => 20,
+            Spell::RepulsiveBlast => 30,
+            Spell::Tornado => 50,
+        }
+    }
+
+    ///what the spell deals to each enemy it hits, 0 for spells that don't hurt or that do it with
+    ///projectiles
+    pub fn damage(self) -> usize {
+        match self {
+            Spell::WaterSpear => 50,
+            Spell::Flame => 10,
+            Spell::Explosion => 40,
+            Spell::AuraOfFire => 30,
+            Spell::RepulsiveBlast => 20,
+            Spell::Tornado => 20,
+            _ => 0,
+        }
+    }
+
+    ///the projectile the spell conjures on `position`, none for spells that don't conjure any
+    pub fn projectile(
+        self,
+        owner: Team,
+        caster: usize,
+        position: Position,
+        direction: Direction,
+    ) -> Option<Projectile> {
+        let (projectile_type, damage, guiding, speed, passable, lifetime) = match self {
+            Spell::Fireball => (ProjectileType::Fireball, 30, true, 3, true, 3),
+            Spell::Spikes => (ProjectileType::Spike, 10, false, 0, true, 6),
+            Spell::Boulder => (ProjectileType::Boulder, 40, true, 2, false, 8),
+            Spell::Wall => (ProjectileType::Wall, 120, false, 0, false, 10),
+            Spell::WindBolt => (ProjectileType::WindBolt, 10, false, 3, true, 5),
+            _ => return None,
+        };
+        Some(Projectile {
+            position,
+            projectile_type,
+            damage,
+            direction,
+            owner,
+            caster,
+            guiding,
+            speed,
+            passable,
+            lifetime,
+        })
+    }
+
+    pub fn spell_input_type(self) -> &'static SpellInputType {
+        match self {
+            Self::IncreasedCirculation | Self::StoneSkin => &SpellInputType::None(&|_, _| false),
+            Self::AuraOfFire => &SpellInputType::None(&|battle, pos| {
+                let wiz = battle.get_current_wizard();
+                let dist = wiz.position.dist(pos);
+                dist.x + dist.y <= 6
+            }),
+            Self::RepulsiveBlast => &SpellInputType::None(&|battle, pos| {
+                let wiz = battle.get_current_wizard();
+                let dist = wiz.position.dist(pos);
+                (dist.x + dist.y <= 6)
+                    && battle.get_entity_at(pos).is_some_and(|e| e.is_projectile())
+            }),
+            Self::Tornado => &SpellInputType::None(&|battle, pos| {
+                let wiz = battle.get_current_wizard();
+                let dist = wiz.position.dist(pos);
+                dist.x + dist.y <= 5
+            }),
+            Self::Fireball | Self::Boulder | Self::WindBolt | Self::Glide => {
+                &SpellInputType::Direction(&|battle, dir, pos| {
+                    battle.get_current_wizard().position.move_in_direction(dir) == pos
+                })
+            }
+            Spell::WaterSpear => &SpellInputType::Direction(&|battle, dir, pos| {
+                Spell::WaterSpear.trace(battle, dir).contains(&pos)
+            }),
+            Spell::Flame => &SpellInputType::Direction(&|battle, dir, pos| {
+                Spell::Flame.trace(battle, dir).contains(&pos)
+            }),
+
+            Spell::Stagnation => &SpellInputType::Position(1, &|_battle, p1, p2| p1 == p2),
+            Spell::Explosion => {
+                &SpellInputType::Position(2, &|_battle, p1, p2| p1.dist(p2).mag() <= 3)
+            }
+
+            Spell::Spikes => {
+                &SpellInputType::Position(3, &|_battle, p1, p2| p1.dist(p2).reduce(usize::max) <= 3)
+            }
+            Spell::Wall => &SpellInputType::Position(0, &|battle, p1, p2| {
+                let wiz = battle.get_current_wizard();
+                let dist = wiz.position.dist(p1).reduce(usize::max);
+                //there's no ring around the caster's own tile
+                if dist == 0 || wiz.position.dist(p2).reduce(usize::max) != dist {
+                    return false;
+                }
+                let s = |center: Position, other: Position| {
+                    let (x, y) = center.signed_dist(other);
+                    match (x > y, -x > y) {
+                        (true, true) => -7 * y + x,
+                        (true, false) => x + y,
+                        (false, true) => -5 * x - y,
+                        (false, false) => 3 * y - x,
+                    }
+                };
+                let s1 = s(wiz.position, p1);
+                let s2 = s(wiz.position, p2);
+                let max = 8 * dist as isize;
+                (s1 - s2).rem_euclid(max).min((s2 - s1).rem_euclid(max)) <= 2
+            }),
+            Self::ManaDrain => &SpellInputType::Position(4, &|_battle, p1, p2| p1 == p2),
+        }
+    }
+
+    ///how far a ray spell reaches and what it goes through, none for spells that aren't rays
+    pub fn ray(self) -> Option<(usize, Piercing)> {
+        match self {
+            Spell::WaterSpear => Some((3, Piercing::Nothing)),
+            Spell::Flame => Some((7, Piercing::Wizards)),
+            _ => None,
+        }
+    }
+
+    ///the tiles a ray spell reaches going `dir` from the caster, up to and including whatever
+    ///stops it
+    pub fn trace(self, battle: &Battle, dir: Direction) -> Vec<Position> {
+        let Some((range, piercing)) = self.ray() else {
+            return vec![];
+        };
+        let mut tiles = vec![];
+        let mut pos = battle.get_current_wizard().position;
+        for _ in 0..range {
+            pos = pos.move_in_direction(dir);
+            tiles.push(pos);
+            let blocked = match battle.get_entity_at(pos) {
+                //the dead are in the way of everything, like they are for walking and projectiles
+                Some(Entity::Wizard(w)) => {
+                    piercing == Piercing::Nothing || battle.get_wizard(w).is_dead()
+                }
+                Some(Entity::Projectile(p)) => !battle.get_projectile(p).passable,
+                None => false,
+            };
+            if blocked {
+                break;
+            }
+        }
+        tiles
+    }
+
+    ///every target the spell could be cast at by the current wizard
+    pub fn targets(self, battle: &Battle) -> Vec<SpellTarget> {
+        match self.spell_input_type() {
+            SpellInputType::None(_) => vec![SpellTarget::None],
+            SpellInputType::Direction(_) => Direction::iter().map(SpellTarget::Direction).collect(),
+            SpellInputType::Position(filter, _) => (0..HEIGHT)
+                .flat_map(|y| (0..WIDTH).map(move |x| Position::from((x, y))))
+                .filter(|pos| SPELL_POSITION_FILTER[*filter](battle, *pos))
+                .map(SpellTarget::Position)
+                .collect(),
+        }
+    }
+
+    ///the tiles the current wizard could hurt with the spell from where they stand, and how much
+    ///by. projectiles are followed in a straight line for their first flight
+    pub fn threat(self, battle: &Battle) -> Vec<(Position, usize)> {
+        let wiz = battle.get_current_wizard();
+        let caster = battle.current_player();
+        let projectile = self.projectile(wiz.team, caster, wiz.position, Direction::Up);
+        let damage = projectile.as_ref().map_or(self.damage(), |p| p.damage);
+        if damage == 0 {
+            return vec![];
+        }
+        self.targets(battle)
+            .into_iter()
+            .flat_map(|target| match (&projectile, target) {
+                (Some(projectile), SpellTarget::Direction(dir)) => (0..=projectile.speed)
+                    .scan(wiz.position, |pos, _| {
+                        *pos = pos.move_in_direction(dir);
+                        Some(*pos)
+                    })
+                    .collect(),
+                //conjured obstacles only go up where nobody stands
+                (Some(_), _) => vec![],
+                _ => self.area(battle, target),
+            })
+            //nobody can be standing where a projectile is
+            .filter(|tile| {
+                !battle
+                    .get_entity_at(*tile)
+                    .is_some_and(|e| e.is_projectile())
+            })
+            .map(|tile| (tile, damage))
+            .collect()
+    }
+
+    ///the tiles the spell's preview marks for this target, which are the ones it affects
+    pub fn area(self, battle: &Battle, target: SpellTarget) -> Vec<Position> {
+        (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| Position::from((x, y))))
+            .filter(|tile| match (self.spell_input_type(), target) {
+                (SpellInputType::None(area), _) => area(battle, *tile),
+                (SpellInputType::Position(_, area), SpellTarget::Position(pos)) => {
+                    area(battle, pos, *tile)
+                }
+                (SpellInputType::Direction(area), SpellTarget::Direction(dir)) => {
+                    area(battle, dir, *tile)
+                }
+                _ => false,
+            })
+            .collect()
+    }
+}
+
+///what a ray spell goes through, impassable projectiles and the dead stop every ray
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piercing {
+    ///stops at the first wizard
+    Nothing,
+    ///only stops at the dead
+    Wizards,
+}
+
+#[derive(EnumIs)]
+pub enum SpellInputType {
+    None(&'static dyn Fn(&Battle, Position) -> bool),
+    Position(usize, &'static dyn Fn(&Battle, Position, Position) -> bool),
+    Direction(&'static dyn Fn(&Battle, Direction, Position) -> bool),
+}
+
+macro_rules! distance_spell_position_filter {
+    ($max:expr) => {
+        &|battle: &Battle, pos: Position| {
+            let wiz = battle.get_current_wizard();
+            let dist = wiz.position.dist(pos);
+            dist.x + dist.y <= ($max)
+        }
+    };
+}
+
+pub type PositionFilter = &'static dyn Fn(&Battle, Position) -> bool;
+
+pub const SPELL_POSITION_FILTER: &[PositionFilter] = &[
+    distance_spell_position_filter!(5),
+    &|battle: &Battle, pos: Position| {
+        let wiz = battle.get_current_wizard();
+        let dist = wiz.position.dist(pos);
+        battle.get_entity_at(pos).is_some_and(|ent| ent.is_wizard())
+            && (wiz.position != pos)
+            && (dist.x + dist.y <= 8)
+    },
+    distance_spell_position_filter!(7),
+    distance_spell_position_filter!(10),
+    &|battle: &Battle, pos: Position| {
+        let wiz = battle.get_current_wizard();
+        let dist = wiz.position.dist(pos);
+        battle.get_entity_at(pos).is_some_and(|ent| match ent {
+            Entity::Wizard(w) => battle.get_wizard(w).team != wiz.team,
+            Entity::Projectile(_) => false,
+        }) && (dist.x + dist.y <= 5)
+    },
+];
+
+#[derive(Debug, Clone, Copy, EnumIter, PartialEq, Eq)]
+pub enum SpellElement {
+    Water,
+    Fire,
+    Earth,
+    Wind,
+}
@@ -0,0 +1,1481 @@
+use std::{fs, time::Duration};
+
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use crate::{
+    action::{Action, SpellTarget},
+    agent::{self, AgentError, ProcessAgent},
+    ai::{self, SEARCH_DEPTH},
+    board::{Board, Entity, WIDTH},
+    effects::Effects,
+    gym::{self, Env, VecEnv},
+    lobby::Discovery,
+    log::{DamageSource, LogEvent},
+    map::Placement,
+    net::{Client, ClientMessage, Handshake, Rejoin, Server, ServerMessage},
+    position::{Direction, Position},
+    projectile::{Projectile, ProjectileType},
+    replay::ReplayViewer,
+    spell::{Spell, SpellElement},
+    wizard::Team,
+    Battle, MatchSetup, Model, Seat, SpellChoice, SpellSelect, MAX_MANA, MAX_PLAYERS,
+};
+
+///a duel that starts right away, on the map's default spawns
+fn fixed_spawns() -> SpellSelect {
+    SpellSelect {
+        placement: Placement::Fixed,
+        ..Default::default()
+    }
+}
+
+///a duel between these builds with the wizards moved onto these tiles
+fn duel_at(builds: [SpellChoice; 2], tiles: [(usize, usize); 2]) -> Battle {
+    let mut battle = Battle::new(&SpellSelect {
+        players: builds.to_vec(),
+        ..fixed_spawns()
+    });
+    for (wizard, tile) in battle.wizards.iter_mut().zip(tiles) {
+        wizard.position = tile.into();
+    }
+    battle.board = Board::new(&battle.wizards, &battle.projectiles);
+    battle.update_valid_move_positons();
+    battle
+}
+
+///puts the projectile its spell would conjure on the tile, cast by the first wizard on the team
+///and standing still
+fn projectile_at(
+    battle: &mut Battle,
+    tile: (usize, usize),
+    projectile_type: ProjectileType,
+    owner: Team,
+) -> &mut Projectile {
+    let caster = battle
+        .wizards
+        .iter()
+        .position(|wizard| wizard.team == owner)
+        .unwrap();
+    let spell = match projectile_type {
+        ProjectileType::Fireball => Spell::Fireball,
+        ProjectileType::Spike => Spell::Spikes,
+        ProjectileType::Boulder => Spell::Boulder,
+        ProjectileType::Wall => Spell::Wall,
+        ProjectileType::WindBolt => Spell::WindBolt,
+    };
+    let projectile = spell
+        .projectile(owner, caster, tile.into(), Direction::Up)
+        .unwrap();
+    battle.projectiles.push(Projectile {
+        guiding: false,
+        speed: 0,
+        ..projectile
+    });
+    battle.board = Board::new(&battle.wizards, &battle.projectiles);
+    battle.update_valid_move_positons();
+    battle.projectiles.last_mut().unwrap()
+}
+
+fn fire() -> SpellChoice {
+    SpellChoice {
+        fire: 4,
+        unused: 2,
+        ..Default::default()
+    }
+}
+
+fn earth() -> SpellChoice {
+    SpellChoice {
+        earth: 4,
+        unused: 2,
+        ..Default::default()
+    }
+}
+
+fn wind() -> SpellChoice {
+    SpellChoice {
+        wind: 4,
+        unused: 2,
+        ..Default::default()
+    }
+}
+
+fn water() -> SpellChoice {
+    SpellChoice {
+        water: 4,
+        unused: 2,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn spell_requirements() {
+    assert_eq!(
+        Spell::iter().map(|spell| spell.requirement()).collect_vec(),
+        SpellElement::iter()
+            .flat_map(|ele| { (1..=4).map(move |i| (ele, i)) })
+            .collect_vec()
+    )
+}
+
+#[test]
+fn end_turn_recovers_mana_and_logs() {
+    let mut battle = Battle::new(&fixed_spawns());
+    battle.apply(Action::EndTurn).unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 0);
+    assert_eq!(battle.turn(), 2);
+    assert_eq!(battle.get_wizard(0).mana, MAX_MANA);
+    assert_eq!(
+        battle
+            .log()
+            .iter()
+            .filter(|entry| entry.event.is_turn_start())
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn save_round_trip() {
+    let mut battle = Battle::new(&fixed_spawns());
+    battle
+        .apply(Action::Move((2_usize, 11_usize).into()))
+        .unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    let save = Model::Battle(Box::new(battle)).to_save_string().unwrap();
+    let Ok(Model::Battle(loaded)) = Model::from_save_str(&save) else {
+        panic!("battle didn't load");
+    };
+    assert_eq!(loaded.current_player(), 1);
+    assert_eq!(loaded.get_wizard(0).position, (2_usize, 11_usize).into());
+    assert!(loaded
+        .get_entity_at((2_usize, 11_usize).into())
+        .is_some_and(|e| e.is_wizard()));
+
+    let fresh = Model::Battle(Box::new(Battle::new(&fixed_spawns())))
+        .to_save_string()
+        .unwrap();
+    //puts the second wizard on top of the first one
+    let overlapping = fresh.replacen("\"x\": 26", "\"x\": 2", 1);
+    assert!(Model::from_save_str(&overlapping).is_err());
+
+    //the state has to be where its actions lead
+    let mut forgetful: serde_json::Value = serde_json::from_str(&save).unwrap();
+    forgetful["state"]["Battle"]["actions"] = serde_json::json!([]);
+    assert!(Model::from_save_str(&forgetful.to_string()).is_err());
+
+    let mut placing = Battle::new(&SpellSelect::default());
+    placing.apply(placing.pass_action()).unwrap();
+    let save = Model::Battle(Box::new(placing)).to_save_string().unwrap();
+    assert!(Model::from_save_str(&save).is_ok());
+    let mut off_board: serde_json::Value = serde_json::from_str(&save).unwrap();
+    off_board["state"]["Battle"]["placements"][0] = serde_json::json!({ "x": 999, "y": 999 });
+    let off_board = off_board.to_string();
+    assert!(Model::from_save_str(&off_board).is_err());
+
+    let mut circulating = duel_at([water(), water()], [(10, 10), (15, 10)]);
+    circulating
+        .apply(Action::Cast(0, SpellTarget::None))
+        .unwrap();
+    let save = Model::Battle(Box::new(circulating))
+        .to_save_string()
+        .unwrap();
+    let mut worn_off: serde_json::Value = serde_json::from_str(&save).unwrap();
+    worn_off["state"]["Battle"]["wizards"][0]["effects"][0]["turns"] = serde_json::json!(0);
+    let Err(error) = Model::from_save_str(&worn_off.to_string()) else {
+        panic!("an effect with no turns left loaded");
+    };
+    assert!(error.to_string().contains("wore off"));
+}
+
+#[test]
+fn replay_resimulates_the_match() {
+    let mut battle = Battle::new(&fixed_spawns());
+    [
+        Action::Move((2_usize, 11_usize).into()),
+        Action::EndTurn,
+        Action::Move((26_usize, 10_usize).into()),
+        Action::EndTurn,
+        Action::EndTurn,
+    ]
+    .into_iter()
+    .for_each(|action| battle.apply(action).unwrap());
+    assert!(battle
+        .apply(Action::Move((5_usize, 5_usize).into()))
+        .is_err());
+
+    let replay = battle.replay();
+    assert_eq!(replay.turn_count(), 3);
+    assert_eq!(replay.step_of_turn(2), 4);
+
+    let mut viewer = ReplayViewer::new(replay.clone()).unwrap();
+    while !viewer.is_finished() {
+        viewer.step_forward();
+    }
+    assert_eq!(
+        viewer.battle().get_wizard(1).position,
+        (26_usize, 10_usize).into()
+    );
+    viewer.jump_to_turn(1);
+    assert_eq!(viewer.step(), 2);
+    assert_eq!(
+        viewer.battle().get_wizard(1).position,
+        (26_usize, 9_usize).into()
+    );
+    viewer.step_back();
+    assert_eq!(viewer.battle().current_player(), 0);
+
+    //a replay whose setup doesn't add up is turned away instead of crashing the viewer
+    let mut broken = replay;
+    broken.setup.teams.pop();
+    assert!(broken.battle_at(0).is_err());
+    assert!(ReplayViewer::new(broken).is_err());
+}
+
+#[test]
+fn undo_only_within_the_turn() {
+    let mut battle = Battle::new(&fixed_spawns());
+    assert!(!battle.can_undo());
+    battle
+        .apply(Action::Move((2_usize, 11_usize).into()))
+        .unwrap();
+    assert!(battle.undo());
+    assert_eq!(battle.get_wizard(0).position, (2_usize, 9_usize).into());
+    assert!(battle.actions().is_empty());
+    assert!(battle
+        .get_entity_at((2_usize, 9_usize).into())
+        .is_some_and(|e| e.is_wizard()));
+
+    battle
+        .apply(Action::Move((2_usize, 10_usize).into()))
+        .unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert!(!battle.can_undo());
+    assert!(!battle.undo());
+    assert_eq!(battle.current_player(), 1);
+}
+
+#[test]
+fn ai_picks_legal_actions() {
+    let build = SpellChoice {
+        fire: 4,
+        wind: 2,
+        unused: 0,
+        ..SpellChoice::default()
+    };
+    let mut battle = Battle::new(&SpellSelect {
+        players: vec![build; 2],
+        ..SpellSelect::default()
+    });
+    for _ in 0..6 {
+        let action = ai::choose_action(&battle, SEARCH_DEPTH);
+        battle.apply(action).unwrap();
+    }
+    assert!(battle.turn() > 0);
+}
+
+#[test]
+fn tiles_off_the_board_are_illegal() {
+    let far = Position::from((3_usize, usize::MAX));
+    let mut placing = Battle::new(&SpellSelect::default());
+    assert!(placing.apply(Action::Place(far)).is_err());
+    let mut battle = duel_at([earth(), earth()], [(10, 10), (15, 10)]);
+    for action in [
+        Action::Move(far),
+        Action::Cast(1, SpellTarget::Position(far)),
+        Action::Move((WIDTH, 10_usize).into()),
+    ] {
+        assert!(battle.is_legal(action).is_err());
+        assert!(battle.apply(action).is_err());
+    }
+    assert!(battle.actions().is_empty());
+}
+
+#[test]
+fn action_text_round_trip() {
+    [
+        Action::Move((3_usize, 4_usize).into()),
+        Action::Cast(0, SpellTarget::None),
+        Action::Cast(1, SpellTarget::Position((10_usize, 2_usize).into())),
+        Action::Cast(2, SpellTarget::Direction(Direction::Left)),
+        Action::EndTurn,
+    ]
+    .into_iter()
+    .for_each(|action| assert_eq!(action.to_string().parse::<Action>(), Ok(action)));
+    assert!("fly 1 2".parse::<Action>().is_err());
+}
+
+fn bot_script(name: &str, script: &str) -> String {
+    let path = std::env::temp_dir().join(format!("wizard-fight-{name}-{}.sh", std::process::id()));
+    fs::write(&path, script).unwrap();
+    format!("sh {}", path.display())
+}
+
+#[test]
+fn process_agent_retries_illegal_actions() {
+    //answers with an illegal move first, then ends its turn once told off
+    let command = bot_script(
+        "illegal",
+        r#"read hello
+echo ready
+answer="action move 20 10"
+while read line; do
+    case "$line" in
+        go*) echo "info thinking"; echo "$answer";;
+        illegal*) answer="action end";;
+        quit) exit;;
+    esac
+done
+"#,
+    );
+    let battle = Battle::new(&fixed_spawns());
+    let mut bot = ProcessAgent::spawn(&command, Duration::from_secs(5)).unwrap();
+    assert_eq!(
+        agent::next_action(&mut bot, &battle).unwrap(),
+        Action::EndTurn
+    );
+}
+
+#[test]
+fn process_agent_times_out() {
+    let command = bot_script(
+        "silent",
+        "read hello\necho ready\nwhile read line; do :; done\n",
+    );
+    let battle = Battle::new(&fixed_spawns());
+    let mut bot = ProcessAgent::spawn(&command, Duration::from_millis(200)).unwrap();
+    assert!(matches!(
+        agent::next_action(&mut bot, &battle),
+        Err(AgentError::Timeout)
+    ));
+}
+
+#[test]
+fn all_builds_spend_every_point() {
+    let builds = SpellChoice::all_builds();
+    assert_eq!(builds.len(), 68);
+    assert!(builds.iter().all(|build| {
+        build.water + build.fire + build.earth + build.wind == 6
+            && [build.water, build.fire, build.earth, build.wind]
+                .iter()
+                .all(|&points| points <= 4)
+    }));
+}
+
+#[test]
+fn play_out_finishes_or_stops_at_the_turn_limit() {
+    let mut battle = Battle::new(&SpellSelect::default());
+    let mut agents: Vec<Box<dyn agent::Agent>> = vec![
+        Box::new(agent::SearchAgent { depth: 1 }),
+        Box::new(agent::SearchAgent { depth: 1 }),
+    ];
+    let failures = agent::play_out(&mut battle, &mut agents, 10);
+    assert_eq!(failures, vec![0, 0]);
+    assert!(battle.is_over() || battle.turn() >= 10);
+}
+
+#[test]
+fn gym_action_mask_matches_legal_actions() {
+    let build = SpellChoice {
+        fire: 4,
+        wind: 2,
+        unused: 0,
+        ..Default::default()
+    };
+    let mut env = Env::new(0, vec![build.clone(); 2]).unwrap();
+    let observation = env.observation();
+    assert_eq!(observation.board.len(), gym::CHANNELS * gym::TILES);
+    let legal = env.battle().legal_actions();
+    let masked = (0..gym::ACTION_COUNT)
+        .filter(|i| observation.action_mask[*i])
+        .map(|i| gym::index_action(env.battle(), i).unwrap())
+        .collect_vec();
+    assert_eq!(masked.len(), legal.len());
+    assert!(masked.iter().all(|action| legal.contains(action)));
+
+    let (observation, reward, done) = env.step(0).unwrap();
+    assert_eq!((observation.player, reward, done), (1, 0.0, false));
+    assert!(env.step(gym::ACTION_COUNT).is_err());
+
+    let mut envs = VecEnv::new(3, 0, vec![build.clone(); 2]).unwrap();
+    for env in &mut envs.envs {
+        env.max_turns = 1;
+    }
+    let steps = envs.step(&[0, 0, 0]);
+    assert!(steps
+        .iter()
+        .all(|step| matches!(step, Ok((obs, _, true)) if obs.player == 0)));
+
+    let unspent = SpellChoice {
+        unused: 2,
+        wind: 0,
+        ..build
+    };
+    assert!(Env::new(0, vec![unspent.clone(), unspent.clone()]).is_err());
+    assert!(VecEnv::new(2, 0, vec![unspent; 2]).is_err());
+}
+
+#[test]
+fn network_host_validates_and_resyncs_clients() {
+    let battle = Battle::new(&SpellSelect {
+        seats: vec![Seat::Human, Seat::Remote],
+        ..fixed_spawns()
+    });
+    let server = Server::host(0, battle).unwrap();
+    let timeout = Duration::from_secs(5);
+    let mut client = Client::connect(("127.0.0.1", server.port()), None).unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Welcome {
+            seat: 1,
+            game: 0,
+            ..
+        })
+    ));
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::State(_))
+    ));
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Joined(1))
+    ));
+
+    client
+        .send(&ClientMessage::Action {
+            step: 0,
+            action: Action::EndTurn,
+        })
+        .unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Rejected(_))
+    ));
+
+    server.send(ClientMessage::Action {
+        step: 0,
+        action: Action::EndTurn,
+    });
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Applied {
+            step: 0,
+            action: Action::EndTurn
+        })
+    ));
+    let rejoin = client.rejoin().unwrap();
+    drop(client);
+
+    //only the client that had the seat gets it back
+    let forged = Rejoin {
+        token: rejoin.token ^ 1,
+        ..rejoin
+    };
+    let mut impostor = Client::connect(("127.0.0.1", server.port()), Some(forged)).unwrap();
+    assert!(matches!(
+        impostor.recv_timeout(timeout),
+        Ok(ServerMessage::Rejected(_))
+    ));
+    let mut client = Client::connect(("127.0.0.1", server.port()), Some(rejoin)).unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Welcome {
+            seat: 1,
+            game: 0,
+            ..
+        })
+    ));
+    let Ok(ServerMessage::State(replay)) = client.recv_timeout(timeout) else {
+        panic!("expected the battle after reconnecting");
+    };
+    assert_eq!(replay.actions, vec![Action::EndTurn]);
+    assert_eq!(server.battle().unwrap().current_player(), 1);
+}
+
+#[test]
+fn network_room_lets_clients_pick_their_own_build() {
+    let room = SpellSelect {
+        seats: vec![Seat::Human, Seat::Remote],
+        ..Default::default()
+    };
+    let server = Server::host_room(0, room.clone()).unwrap();
+    let timeout = Duration::from_secs(5);
+    let mut client = Client::connect(("127.0.0.1", server.port()), None).unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Welcome {
+            seat: 1,
+            game: 0,
+            ..
+        })
+    ));
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Room(_))
+    ));
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Joined(1))
+    ));
+
+    let build = SpellChoice {
+        water: 4,
+        earth: 2,
+        unused: 0,
+        ..Default::default()
+    };
+    client
+        .send(&ClientMessage::Build(0, build.clone()))
+        .unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Rejected(_))
+    ));
+    client
+        .send(&ClientMessage::Build(1, build.clone()))
+        .unwrap();
+    let Ok(ServerMessage::Room(mirrored)) = client.recv_timeout(timeout) else {
+        panic!("expected the room with the new build");
+    };
+    assert_eq!(mirrored.players[1], build);
+    assert!(server.start().is_err());
+
+    //the host can't overwrite the client's build
+    let mut host_room = room;
+    host_room.players[0] = build.clone();
+    server.set_room(host_room);
+    server.start().unwrap();
+    let battle = server.battle().unwrap();
+    assert_eq!(battle.setup().players, vec![build.clone(), build]);
+
+    let discovery = Discovery::listen_on(0).unwrap();
+    server
+        .announce_to("test host".to_owned(), discovery.port())
+        .unwrap();
+    let start = std::time::Instant::now();
+    while discovery.games().is_empty() && start.elapsed() < timeout {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let games = discovery.games();
+    assert_eq!(games[0].announcement.name, "test host");
+    assert_eq!(games[0].addr.port(), server.port());
+    assert!(games[0].announcement.started);
+}
+
+#[test]
+fn network_room_server_seats_adopted_clients_and_times_out_turns() {
+    let build = SpellChoice {
+        fire: 4,
+        wind: 2,
+        unused: 0,
+        ..Default::default()
+    };
+    let server = Server::room(
+        SpellSelect {
+            players: vec![build.clone(), build],
+            seats: vec![Seat::Remote, Seat::Remote],
+            ..Default::default()
+        },
+        7,
+    );
+    server.set_turn_time(Duration::from_millis(200));
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let timeout = Duration::from_secs(5);
+
+    let mut clients = vec![];
+    for seat in 0..2 {
+        let client = Client::connect(("127.0.0.1", port), None).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        server.adopt(Handshake::read(stream).unwrap());
+        clients.push(client);
+        assert_eq!(server.free_seats(), 1 - seat);
+    }
+    for (seat, client) in clients.iter_mut().enumerate() {
+        let Ok(ServerMessage::Welcome {
+            seat: given, game, ..
+        }) = client.recv_timeout(timeout)
+        else {
+            panic!("expected a welcome");
+        };
+        assert_eq!((given, game), (seat, 7));
+    }
+    server.start().unwrap();
+
+    //nobody does anything, so the timer picks their starting tiles and ends the first turn
+    let start = std::time::Instant::now();
+    while server.battle().unwrap().turn() == 0 && start.elapsed() < timeout {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(matches!(
+        server.battle().unwrap().actions(),
+        [Action::Place(_), Action::Place(_), Action::EndTurn]
+    ));
+}
+
+#[test]
+fn network_spectators_watch_a_delayed_battle() {
+    let battle = Battle::new(&SpellSelect {
+        seats: vec![Seat::Human, Seat::Human],
+        ..fixed_spawns()
+    });
+    let server = Server::host(0, battle).unwrap();
+    server.set_spectator_delay(1);
+    let timeout = Duration::from_secs(5);
+    let mut spectator = Client::watch(("127.0.0.1", server.port()), None).unwrap();
+    assert!(matches!(
+        spectator.recv_timeout(timeout),
+        Ok(ServerMessage::Watching { game: 0, delay: 1 })
+    ));
+    assert!(matches!(
+        spectator.recv_timeout(timeout),
+        Ok(ServerMessage::State(replay)) if replay.actions.is_empty()
+    ));
+    assert_eq!(spectator.seat(), None);
+
+    spectator
+        .send(&ClientMessage::Action {
+            step: 0,
+            action: Action::EndTurn,
+        })
+        .unwrap();
+    assert!(matches!(
+        spectator.recv_timeout(timeout),
+        Ok(ServerMessage::Rejected(_))
+    ));
+
+    //the first turn only shows up once the second one is over
+    for step in 0..2 {
+        server.send(ClientMessage::Action {
+            step,
+            action: Action::EndTurn,
+        });
+    }
+    assert!(matches!(
+        spectator.recv_timeout(timeout),
+        Ok(ServerMessage::Applied {
+            step: 0,
+            action: Action::EndTurn
+        })
+    ));
+    assert!(spectator.recv_timeout(Duration::from_millis(200)).is_err());
+    assert_eq!(server.spectators(), 1);
+}
+
+#[test]
+fn network_clients_only_see_their_own_starting_tiles() {
+    let battle = Battle::new(&SpellSelect {
+        seats: vec![Seat::Human, Seat::Remote],
+        ..Default::default()
+    });
+    let server = Server::host(0, battle).unwrap();
+    let timeout = Duration::from_secs(5);
+    let picked = Action::Place((3_usize, 8_usize).into());
+    server.send(ClientMessage::Action {
+        step: 0,
+        action: picked,
+    });
+    let mut client = Client::connect(("127.0.0.1", server.port()), None).unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Welcome { seat: 1, .. })
+    ));
+    let Ok(ServerMessage::State(replay)) = client.recv_timeout(timeout) else {
+        panic!("expected the battle");
+    };
+    assert_eq!(replay.actions.len(), 1);
+    assert_ne!(replay.actions[0], picked);
+    assert!(replay.battle_at(1).unwrap().is_placing());
+
+    //the last pick reveals the real tiles
+    client
+        .send(&ClientMessage::Action {
+            step: 1,
+            action: Action::Place((25_usize, 10_usize).into()),
+        })
+        .unwrap();
+    let revealed = loop {
+        match client.recv_timeout(timeout) {
+            Ok(ServerMessage::State(replay)) => break replay,
+            Ok(_) => continue,
+            Err(e) => panic!("expected the revealed battle: {e}"),
+        }
+    };
+    assert_eq!(revealed.actions[0], picked);
+    assert_eq!(
+        revealed.battle_at(2).unwrap().get_wizard(0).position,
+        (3_usize, 8_usize).into()
+    );
+
+    //nor does the host see the clients' picks
+    let battle = Battle::new(&SpellSelect {
+        seats: vec![Seat::Remote, Seat::Human],
+        ..Default::default()
+    });
+    let server = Server::host(0, battle).unwrap();
+    let mut client = Client::connect(("127.0.0.1", server.port()), None).unwrap();
+    assert!(matches!(
+        client.recv_timeout(timeout),
+        Ok(ServerMessage::Welcome { seat: 0, .. })
+    ));
+    client
+        .send(&ClientMessage::Action {
+            step: 0,
+            action: picked,
+        })
+        .unwrap();
+    let start = std::time::Instant::now();
+    let echoed = loop {
+        match server.try_recv() {
+            Some(ServerMessage::Applied { step: 0, action }) => break action,
+            _ if start.elapsed() < timeout => std::thread::sleep(Duration::from_millis(20)),
+            _ => panic!("expected the host to hear about the pick"),
+        }
+    };
+    assert_ne!(echoed, picked);
+}
+
+#[test]
+fn teams_take_turns_and_win_together() {
+    let mut battle = Battle::new(&SpellSelect {
+        players: vec![SpellChoice::default(); 4],
+        seats: vec![Seat::Human; 4],
+        teams: vec![Team::Red, Team::Red, Team::Blue, Team::Blue],
+        placement: Placement::Fixed,
+    });
+    assert_eq!(battle.setup().turn_order(), vec![0, 2, 1, 3]);
+    let mut order = vec![battle.current_player()];
+    for _ in 0..4 {
+        battle.apply(Action::EndTurn).unwrap();
+        order.push(battle.current_player());
+    }
+    assert_eq!(order, vec![0, 2, 1, 3, 0]);
+
+    //a teammate's projectile is picked up, an enemy's hurts
+    let start = battle.get_wizard(0).position;
+    let (friendly, hostile) = (start.move_in_direction(Direction::Up), start);
+    projectile_at(
+        &mut battle,
+        friendly.into(),
+        ProjectileType::WindBolt,
+        Team::Red,
+    );
+    battle.apply(Action::Move(friendly)).unwrap();
+    assert_eq!(battle.get_wizard(0).health, 100);
+    assert!(battle.projectiles.is_empty());
+    projectile_at(
+        &mut battle,
+        hostile.into(),
+        ProjectileType::WindBolt,
+        Team::Blue,
+    );
+    battle.move_wizard_to(0, hostile);
+    assert_eq!(battle.get_wizard(0).health, 90);
+
+    battle.damage_wizard(2, 100, DamageSource::Projectile(ProjectileType::WindBolt));
+    assert_eq!(battle.winner(), None);
+    battle.damage_wizard(3, 100, DamageSource::Projectile(ProjectileType::WindBolt));
+    assert_eq!(battle.winner(), Some(Team::Red));
+
+    let mut free_for_all = SpellSelect::default();
+    (0..MAX_PLAYERS).for_each(|_| free_for_all.add_player());
+    assert!(free_for_all.is_playable());
+    let spawns = MatchSetup::new(&free_for_all)
+        .map
+        .spawns(&free_for_all.teams);
+    assert!(spawns.iter().all_unique());
+    assert!(spawns.iter().all(|pos| Board::is_in_bounds(*pos)));
+}
+
+#[test]
+fn projectiles_move_once_a_round_in_team_matches() {
+    let mut battle = Battle::new(&SpellSelect {
+        players: vec![SpellChoice::default(); 4],
+        seats: vec![Seat::Human; 4],
+        teams: vec![Team::Red, Team::Red, Team::Blue, Team::Blue],
+        placement: Placement::Fixed,
+    });
+    let bolt = projectile_at(&mut battle, (14, 2), ProjectileType::WindBolt, Team::Red);
+    bolt.direction = Direction::Right;
+    bolt.speed = 1;
+    for _ in 0..4 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert_eq!(battle.current_player(), 0);
+    assert_eq!(battle.projectiles[0].position, (15_usize, 2_usize).into());
+    assert_eq!(battle.projectiles[0].lifetime, 4);
+
+    //once the caster is gone a teammate's turns move it instead
+    battle.damage_wizard(0, 100, DamageSource::Projectile(ProjectileType::WindBolt));
+    for _ in 0..4 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert_eq!(battle.projectiles[0].position, (16_usize, 2_usize).into());
+    assert_eq!(battle.projectiles[0].lifetime, 3);
+}
+
+#[test]
+fn wizards_pick_hidden_starting_tiles() {
+    let mut battle = Battle::new(&SpellSelect::default());
+    assert!(battle.is_placing());
+    assert!(battle.apply(Action::EndTurn).is_err());
+    assert!(battle
+        .apply(Action::Place((14_usize, 9_usize).into()))
+        .is_err());
+    battle
+        .apply(Action::Place((3_usize, 8_usize).into()))
+        .unwrap();
+    assert!(!battle.can_undo());
+    //red's tile stays hidden until blue has picked too
+    assert_eq!(battle.current_player(), 1);
+    assert_eq!(battle.get_wizard(0).position, (2_usize, 9_usize).into());
+    battle
+        .apply(Action::Place((25_usize, 10_usize).into()))
+        .unwrap();
+    assert!(!battle.is_placing());
+    assert_eq!(battle.current_player(), 0);
+    assert_eq!(battle.get_wizard(0).position, (3_usize, 8_usize).into());
+    assert!(battle
+        .get_entity_at((25_usize, 10_usize).into())
+        .is_some_and(|e| e.is_wizard()));
+    assert!(battle
+        .apply(Action::Place((2_usize, 9_usize).into()))
+        .is_err());
+    battle
+        .apply(Action::Move((4_usize, 8_usize).into()))
+        .unwrap();
+
+    let mut mirrored = Battle::new(&SpellSelect {
+        placement: Placement::Mirrored,
+        ..Default::default()
+    });
+    mirrored
+        .apply(Action::Place((3_usize, 8_usize).into()))
+        .unwrap();
+    assert!(!mirrored.is_placing());
+    assert_eq!(mirrored.get_wizard(1).position, (25_usize, 10_usize).into());
+
+    let setup = MatchSetup::new(&SpellSelect {
+        placement: Placement::Random,
+        ..Default::default()
+    });
+    let random = Battle::from_setup(setup.clone());
+    assert!(!random.is_placing());
+    assert!(random.wizards().iter().enumerate().all(|(i, wiz)| setup
+        .map
+        .spawn_zone(&setup.teams, i)
+        .contains(&wiz.position)));
+    assert_eq!(
+        Battle::from_setup(setup).wizards()[0].position,
+        random.wizards()[0].position
+    );
+}
+
+#[test]
+fn scheduler_skips_delays_and_inserts_turns() {
+    let mut battle = Battle::new(&SpellSelect {
+        players: vec![SpellChoice::default(); 3],
+        seats: vec![Seat::Human; 3],
+        teams: vec![Team::Red, Team::Blue, Team::Green],
+        placement: Placement::Fixed,
+    });
+    assert_eq!(battle.current_player(), 0);
+    battle.skip_turns(1, 2);
+    assert!(battle.get_wizard(1).has_effect(Effects::Stagnant));
+    assert_eq!(battle.upcoming_turns(4), vec![2, 0, 2, 0]);
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 2);
+    assert!(battle
+        .log()
+        .iter()
+        .any(|entry| matches!(entry.event, LogEvent::TurnSkipped { wizard: 1 })));
+    battle.apply(Action::EndTurn).unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 2);
+    assert!(!battle.get_wizard(1).has_effect(Effects::Stagnant));
+    assert_eq!(battle.upcoming_turns(3), vec![0, 1, 2]);
+
+    battle.insert_turn(1);
+    assert_eq!(battle.upcoming_turns(3), vec![1, 0, 1]);
+    //the red wizard waits for two turns to go by, which are both blue's now
+    battle.delay_turn(0, 2);
+    assert_eq!(battle.upcoming_turns(4), vec![1, 1, 0, 2]);
+
+    //the dead drop out of the rotation, so red's delayed turn runs into its usual one
+    battle.damage_wizard(2, 100, DamageSource::Projectile(ProjectileType::Fireball));
+    assert_eq!(battle.upcoming_turns(5), vec![1, 1, 0, 0, 1]);
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 1);
+}
+
+#[test]
+fn increased_circulation_doubles_movement_for_three_turns() {
+    let mut battle = duel_at([water(), water()], [(10, 10), (20, 10)]);
+    assert!(!battle.wizard_can_move((10_usize, 14_usize).into()));
+    battle.apply(Action::Cast(0, SpellTarget::None)).unwrap();
+    assert!(battle.wizard_can_move((10_usize, 14_usize).into()));
+    for _ in 0..4 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert!(battle.wizard_can_move((10_usize, 14_usize).into()));
+    battle.apply(Action::EndTurn).unwrap();
+    assert!(!battle.get_wizard(0).has_effect(Effects::Circulation));
+}
+
+#[test]
+fn water_spear_hits_enemies_three_tiles_out() {
+    let mut battle = duel_at([water(), water()], [(10, 10), (13, 10)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 50);
+    assert_eq!(battle.get_wizard(0).mana, 70);
+
+    let mut battle = duel_at([water(), water()], [(10, 10), (14, 10)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 100);
+}
+
+#[test]
+fn mana_drain_takes_up_to_thirty_mana_from_an_enemy() {
+    let mut battle = duel_at([water(), water()], [(10, 10), (13, 12)]);
+    battle.wizards[1].mana = 20;
+    assert!(battle
+        .apply(Action::Cast(
+            2,
+            SpellTarget::Position((10_usize, 10_usize).into())
+        ))
+        .is_err());
+    battle
+        .apply(Action::Cast(
+            2,
+            SpellTarget::Position((13_usize, 12_usize).into()),
+        ))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).mana, 0);
+    assert_eq!(battle.get_wizard(0).mana, 100);
+
+    let battle = duel_at([water(), water()], [(10, 10), (14, 12)]);
+    assert!(battle
+        .is_legal(Action::Cast(
+            2,
+            SpellTarget::Position((14_usize, 12_usize).into())
+        ))
+        .is_err());
+}
+
+#[test]
+fn stagnation_skips_the_targets_next_two_turns() {
+    let mut battle = duel_at([water(), water()], [(10, 10), (16, 12)]);
+    battle
+        .apply(Action::Cast(
+            3,
+            SpellTarget::Position((16_usize, 12_usize).into()),
+        ))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).effect_turns(Effects::Stagnant), 2);
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 0);
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 0);
+    assert!(!battle.get_wizard(1).has_effect(Effects::Stagnant));
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 1);
+
+    let battle = duel_at([water(), water()], [(10, 10), (16, 13)]);
+    assert!(battle
+        .is_legal(Action::Cast(
+            3,
+            SpellTarget::Position((16_usize, 13_usize).into())
+        ))
+        .is_err());
+
+    //a turn passed up by a delay doesn't count as one sat out
+    let mut battle = duel_at([water(), water()], [(10, 10), (16, 12)]);
+    battle.skip_turns(1, 1);
+    battle.delay_turn(1, 1);
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 0);
+    assert_eq!(battle.get_wizard(1).effect_turns(Effects::Stagnant), 1);
+    battle.apply(Action::EndTurn).unwrap();
+    assert!(!battle.get_wizard(1).has_effect(Effects::Stagnant));
+}
+
+#[test]
+fn flame_burns_along_seven_tiles() {
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (17, 10)]);
+    battle
+        .apply(Action::Cast(0, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 90);
+
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (18, 10)]);
+    battle
+        .apply(Action::Cast(0, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 100);
+}
+
+#[test]
+fn fireballs_home_in_and_burn_out() {
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (13, 16)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Down)))
+        .unwrap();
+    assert_eq!(battle.projectiles[0].position, (10_usize, 11_usize).into());
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.projectiles[0].position, (11_usize, 13_usize).into());
+    //it only moves at the end of its caster's turns
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.projectiles[0].position, (11_usize, 13_usize).into());
+    battle.apply(Action::EndTurn).unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.get_wizard(1).health, 70);
+    assert!(battle.projectiles.is_empty());
+
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (25, 10)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    for _ in 0..4 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert_eq!(battle.projectiles[0].lifetime, 1);
+    battle.apply(Action::EndTurn).unwrap();
+    assert!(battle.projectiles.is_empty());
+    assert_eq!(battle.get_wizard(1).health, 100);
+}
+
+#[test]
+fn explosion_hits_wizards_and_projectiles() {
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (15, 12)]);
+    projectile_at(&mut battle, (16, 12), ProjectileType::Fireball, Team::Blue);
+    projectile_at(&mut battle, (14, 12), ProjectileType::Fireball, Team::Red);
+    battle
+        .apply(Action::Cast(
+            2,
+            SpellTarget::Position((15_usize, 12_usize).into()),
+        ))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 60);
+    assert_eq!(battle.projectiles.len(), 1);
+    assert_eq!(battle.projectiles[0].owner, Team::Red);
+    assert!(battle
+        .is_legal(Action::Cast(
+            2,
+            SpellTarget::Position((15_usize, 13_usize).into())
+        ))
+        .is_err());
+}
+
+#[test]
+fn aura_of_fire_burns_around_the_caster_for_three_turns() {
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (14, 12)]);
+    battle.apply(Action::Cast(3, SpellTarget::None)).unwrap();
+    assert!(battle.wizard_can_move((10_usize, 11_usize).into()));
+    assert!(!battle.wizard_can_move((10_usize, 12_usize).into()));
+    for _ in 0..8 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert_eq!(battle.get_wizard(1).health, 10);
+    assert!(!battle.get_wizard(0).has_effect(Effects::AuraOfFire));
+}
+
+#[test]
+fn stone_skin_prevents_twenty_damage_per_hit() {
+    let mut battle = duel_at([earth(), earth()], [(10, 10), (20, 10)]);
+    battle.apply(Action::Cast(0, SpellTarget::None)).unwrap();
+    let source = DamageSource::Projectile(ProjectileType::WindBolt);
+    battle.damage_wizard(0, 30, source);
+    battle.damage_wizard(0, 15, source);
+    assert_eq!(battle.get_wizard(0).health, 90);
+}
+
+#[test]
+fn spikes_cover_a_patch_and_hurt_whoever_steps_in() {
+    let mut battle = duel_at([earth(), earth()], [(10, 10), (15, 10)]);
+    battle
+        .apply(Action::Cast(
+            1,
+            SpellTarget::Position((14_usize, 10_usize).into()),
+        ))
+        .unwrap();
+    //none go up under the blue wizard
+    assert_eq!(battle.get_wizard(1).health, 100);
+    assert_eq!(battle.projectiles.len(), 48);
+    assert!(battle
+        .get_entity_at((15_usize, 10_usize).into())
+        .is_some_and(|e| e.is_wizard()));
+    battle.apply(Action::EndTurn).unwrap();
+    battle
+        .apply(Action::Move((15_usize, 11_usize).into()))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 90);
+
+    //nor does a wall, right next to them or on them
+    let mut battle = duel_at([earth(), earth()], [(10, 10), (12, 11)]);
+    let target = SpellTarget::Position((12_usize, 10_usize).into());
+    assert!(Spell::Wall
+        .area(&battle, target)
+        .contains(&(12_usize, 11_usize).into()));
+    battle.apply(Action::Cast(3, target)).unwrap();
+    assert_eq!(battle.get_wizard(1).health, 100);
+    assert_eq!(battle.projectiles.len(), 4);
+}
+
+#[test]
+fn boulders_home_in_and_block_the_way() {
+    let mut battle = duel_at([earth(), earth()], [(10, 10), (10, 15)]);
+    battle
+        .apply(Action::Cast(2, SpellTarget::Direction(Direction::Down)))
+        .unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.projectiles[0].position, (10_usize, 13_usize).into());
+    assert!(!battle.wizard_can_move((10_usize, 13_usize).into()));
+    battle.apply(Action::EndTurn).unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.get_wizard(1).health, 60);
+    assert!(battle.projectiles.is_empty());
+}
+
+#[test]
+fn walls_go_up_along_the_previewed_ring() {
+    let mut battle = duel_at([earth(), earth()], [(10, 10), (20, 10)]);
+    let target = SpellTarget::Position((12_usize, 10_usize).into());
+    let ring = Spell::Wall.area(&battle, target);
+    assert_eq!(ring.len(), 5);
+    battle.apply(Action::Cast(3, target)).unwrap();
+    assert_eq!(
+        battle.projectiles.iter().map(|p| p.position).collect_vec(),
+        ring
+    );
+    assert!(battle
+        .projectiles
+        .iter()
+        .all(|p| !p.passable && p.damage == 120));
+    assert!(!battle.wizard_can_move((12_usize, 10_usize).into()));
+    //there's no ring around the caster's own tile
+    assert!(Spell::Wall
+        .area(&battle, SpellTarget::Position((10_usize, 10_usize).into()))
+        .is_empty());
+}
+
+#[test]
+fn wind_bolts_fly_straight() {
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (20, 10)]);
+    battle
+        .apply(Action::Cast(0, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.projectiles[0].position, (14_usize, 10_usize).into());
+    for _ in 0..4 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert_eq!(battle.get_wizard(1).health, 90);
+    assert!(battle.projectiles.is_empty());
+}
+
+#[test]
+fn glide_stops_in_front_of_obstacles() {
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (15, 10)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(0).position, (14_usize, 10_usize).into());
+
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (15, 10)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Down)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(0).position, (10_usize, 17_usize).into());
+    //gliding is a spell, so there's still a move left
+    assert!(battle.wizard_can_move((10_usize, 19_usize).into()));
+}
+
+#[test]
+fn repulsive_blast_pushes_enemies_and_hits_projectiles() {
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (12, 10)]);
+    let walls = |battle: &mut Battle, passable: (usize, usize)| {
+        projectile_at(battle, (15, 10), ProjectileType::Wall, Team::Red).damage = 60;
+        let wall = projectile_at(battle, passable, ProjectileType::Wall, Team::Blue);
+        wall.damage = 60;
+        wall.passable = true;
+    };
+    walls(&mut battle, (10, 13));
+    battle.apply(Action::Cast(2, SpellTarget::None)).unwrap();
+    assert_eq!(battle.get_wizard(1).position, (14_usize, 10_usize).into());
+    assert_eq!(battle.projectiles[0].damage, 60);
+    assert_eq!(battle.projectiles[1].damage, 20);
+
+    //the blast goes off before the push, so landing where a projectile was doesn't hurt
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (12, 10)]);
+    walls(&mut battle, (14, 10));
+    battle.apply(Action::Cast(2, SpellTarget::None)).unwrap();
+    assert_eq!(battle.get_wizard(1).position, (14_usize, 10_usize).into());
+    assert_eq!(battle.get_wizard(1).health, 100);
+}
+
+#[test]
+fn tornado_spins_for_two_turns() {
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (13, 11)]);
+    battle.apply(Action::Cast(3, SpellTarget::None)).unwrap();
+    assert!(battle.wizard_can_move((10_usize, 14_usize).into()));
+    battle.apply(Action::EndTurn).unwrap();
+    //resting gives 20 mana, halved
+    assert_eq!(battle.get_wizard(0).mana, 60);
+    for _ in 0..3 {
+        battle.apply(Action::EndTurn).unwrap();
+    }
+    assert_eq!(battle.get_wizard(1).health, 60);
+    assert!(!battle.get_wizard(0).has_effect(Effects::Tornado));
+}
+
+#[test]
+fn forced_movement_steps_through_hazards_and_collides() {
+    let mut battle = duel_at([wind(), wind()], [(10, 10), (12, 10)]);
+    projectile_at(&mut battle, (13, 10), ProjectileType::Spike, Team::Red);
+    projectile_at(&mut battle, (16, 10), ProjectileType::Wall, Team::Red);
+    battle.force_move(Entity::Wizard(1), Direction::Right, 6, Some(15));
+    assert_eq!(battle.get_wizard(1).position, (15_usize, 10_usize).into());
+    //10 from the spike it ran over and 15 from the wall
+    assert_eq!(battle.get_wizard(1).health, 75);
+    assert_eq!(battle.projectiles.len(), 1);
+    assert_eq!(battle.projectiles[0].damage, 90);
+    assert_eq!(
+        battle
+            .log()
+            .iter()
+            .filter(|entry| entry.event.is_slid())
+            .count(),
+        3
+    );
+
+    //projectiles stop at anything, and without collision damage nobody gets hurt
+    battle.force_move(Entity::Projectile(0), Direction::Left, 3, None);
+    assert_eq!(battle.projectiles[0].position, (16_usize, 10_usize).into());
+    battle.force_move(Entity::Projectile(0), Direction::Down, 2, None);
+    assert_eq!(battle.projectiles[0].position, (16_usize, 12_usize).into());
+    assert_eq!(battle.get_wizard(1).health, 75);
+}
+
+#[test]
+fn effects_stack_by_their_rules_and_remember_who_cast_them() {
+    let mut battle = duel_at([water(), water()], [(10, 10), (16, 12)]);
+    battle.apply_effect(1, Effects::StoneSkin, 1, 3);
+    battle.apply_effect(1, Effects::StoneSkin, 0, 1);
+    //refreshing replaces the turns and the source
+    assert_eq!(
+        battle
+            .get_wizard(1)
+            .effect(Effects::StoneSkin)
+            .unwrap()
+            .turns,
+        1
+    );
+    assert_eq!(
+        battle
+            .get_wizard(1)
+            .effect(Effects::StoneSkin)
+            .unwrap()
+            .source,
+        0
+    );
+
+    battle.skip_turns(1, 1);
+    battle
+        .apply(Action::Cast(
+            3,
+            SpellTarget::Position((16_usize, 12_usize).into()),
+        ))
+        .unwrap();
+    //stagnation stacks, and so do the turns sat out
+    assert_eq!(battle.get_wizard(1).effect_turns(Effects::Stagnant), 3);
+    assert_eq!(battle.wizards[1].effects.len(), 2);
+    for _ in 0..3 {
+        battle.apply(Action::EndTurn).unwrap();
+        assert_eq!(battle.current_player(), 0);
+    }
+    assert!(!battle.get_wizard(1).has_effect(Effects::Stagnant));
+    //stone skin only ticks down on turns the wizard takes
+    assert!(battle.get_wizard(1).has_effect(Effects::StoneSkin));
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.current_player(), 1);
+    battle.apply(Action::EndTurn).unwrap();
+    assert!(battle.get_wizard(1).effects.is_empty());
+}
+
+#[test]
+fn rays_stop_at_walls_and_spears_at_the_first_wizard() {
+    let mut battle = duel_at([water(), water()], [(10, 10), (12, 10)]);
+    let tiles = |tiles: &[(usize, usize)]| tiles.iter().map(|&t| t.into()).collect_vec();
+    assert_eq!(
+        Spell::WaterSpear.trace(&battle, Direction::Right),
+        tiles(&[(11, 10), (12, 10)])
+    );
+    assert_eq!(
+        Spell::Flame.trace(&battle, Direction::Right),
+        tiles(&[
+            (11, 10),
+            (12, 10),
+            (13, 10),
+            (14, 10),
+            (15, 10),
+            (16, 10),
+            (17, 10)
+        ])
+    );
+
+    projectile_at(&mut battle, (11, 10), ProjectileType::Wall, Team::Red);
+    assert_eq!(
+        Spell::Flame.trace(&battle, Direction::Right),
+        tiles(&[(11, 10)])
+    );
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    assert_eq!(battle.get_wizard(1).health, 100);
+}
+
+#[test]
+fn the_dead_block_rays_walking_and_projectiles_alike() {
+    let mut battle = Battle::new(&SpellSelect {
+        players: vec![fire(); 3],
+        seats: vec![Seat::Human; 3],
+        teams: vec![Team::Red, Team::Blue, Team::Green],
+        placement: Placement::Fixed,
+    });
+    for (wizard, tile) in [(10_usize, 10_usize), (12, 10), (14, 10)]
+        .into_iter()
+        .enumerate()
+    {
+        battle.wizards[wizard].position = tile.into();
+    }
+    let bolt = projectile_at(&mut battle, (11, 10), ProjectileType::WindBolt, Team::Red);
+    bolt.direction = Direction::Right;
+    bolt.speed = 3;
+    battle.damage_wizard(1, 100, DamageSource::Collision);
+    battle.update_valid_move_positons();
+
+    let corpse = Position::from((12_usize, 10_usize));
+    assert!(!battle.wizard_can_move(corpse));
+    assert_eq!(
+        Spell::Flame.trace(&battle, Direction::Right),
+        vec![(11_usize, 10_usize).into(), corpse]
+    );
+    battle.apply(Action::EndTurn).unwrap();
+    assert_eq!(battle.projectiles[0].position, (11_usize, 10_usize).into());
+    assert_eq!(battle.get_wizard(2).health, 100);
+
+    //nor does one cast right at them hit them
+    assert_eq!(battle.current_player(), 2);
+    battle
+        .apply(Action::Move((13_usize, 10_usize).into()))
+        .unwrap();
+    let hits = battle.log().len();
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Left)))
+        .unwrap();
+    assert!(!battle.log()[hits..]
+        .iter()
+        .any(|entry| entry.event.is_wizard_damaged()));
+    assert_eq!(battle.projectiles.len(), 1);
+}
+
+#[test]
+fn forecasts_match_what_the_spell_then_does() {
+    let mut battle = duel_at([fire(), water()], [(10, 10), (15, 12)]);
+    projectile_at(&mut battle, (16, 12), ProjectileType::Fireball, Team::Blue);
+    let explosion = Action::Cast(2, SpellTarget::Position((15_usize, 12_usize).into()));
+    let forecast = battle.forecast(explosion).unwrap();
+    //the caster pays for it and the enemy gets hit
+    assert_eq!(forecast.wizards.len(), 2);
+    assert_eq!(forecast.wizards[0].mana, (100, 50));
+    assert_eq!(forecast.wizards[1].health, (100, 60));
+    assert_eq!(forecast.projectiles.len(), 1);
+    assert_eq!(forecast.projectiles[0].damage, (30, None));
+    //nothing happened yet
+    assert_eq!(battle.get_wizard(1).health, 100);
+    assert_eq!(battle.projectiles.len(), 1);
+
+    battle.apply(explosion).unwrap();
+    assert_eq!(battle.get_wizard(1).health, forecast.wizards[1].health.1);
+    assert!(battle.projectiles.is_empty());
+
+    let battle = duel_at([water(), water()], [(10, 10), (16, 12)]);
+    let stagnation = Action::Cast(3, SpellTarget::Position((16_usize, 12_usize).into()));
+    let forecast = battle.forecast(stagnation).unwrap();
+    assert_eq!(forecast.wizards[1].effects[0].kind, Effects::Stagnant);
+    assert_eq!(forecast.wizards[1].effects[0].source, 0);
+    assert!(battle
+        .forecast(Action::Cast(
+            3,
+            SpellTarget::Position((17_usize, 12_usize).into())
+        ))
+        .is_err());
+}
+
+#[test]
+fn threats_cover_what_enemies_can_reach_and_afford() {
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (15, 10)]);
+    let threats = battle.threats(Team::Red);
+    //the worst blue can do is an explosion
+    assert_eq!(threats.damage.get(&(10_usize, 10_usize).into()), Some(&40));
+    assert_eq!(threats.damage.get(&(0_usize, 0_usize).into()), None);
+    assert!(threats.reach.contains(&(17_usize, 10_usize).into()));
+    assert!(!threats.reach.contains(&(18_usize, 10_usize).into()));
+    assert_eq!(battle.reach(1), threats.reach);
+    assert!(!battle.threats(Team::Blue).damage.is_empty());
+
+    battle.wizards[1].mana = 0;
+    let threats = battle.threats(Team::Red);
+    assert!(threats.damage.is_empty());
+    assert!(!threats.reach.is_empty());
+}
+
+#[test]
+fn trajectories_follow_homing_projectiles_to_where_they_end() {
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (13, 16)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Down)))
+        .unwrap();
+    let trajectories = battle.trajectories(5, None);
+    assert_eq!(trajectories.len(), 1);
+    assert_eq!(trajectories[0].stops[0], ((11_usize, 13_usize).into(), 2));
+    assert_eq!(trajectories[0].end, Some((13_usize, 16_usize).into()));
+    assert_eq!(
+        trajectories[0].path.last(),
+        Some(&(13_usize, 16_usize).into())
+    );
+    //predicting doesn't touch the battle
+    assert_eq!(battle.projectiles[0].position, (10_usize, 11_usize).into());
+
+    //on blue's turn the fireball chases wherever blue is about to walk
+    battle.apply(Action::EndTurn).unwrap();
+    let planned = (13_usize, 14_usize).into();
+    assert_eq!(battle.trajectories(4, Some(planned))[0].end, Some(planned));
+    assert_eq!(
+        battle.trajectories(4, None)[0].end,
+        Some((13_usize, 16_usize).into())
+    );
+    //a fireball that can't reach anyone burns out on its last tile
+    let mut battle = duel_at([fire(), fire()], [(10, 10), (25, 10)]);
+    battle
+        .apply(Action::Cast(1, SpellTarget::Direction(Direction::Right)))
+        .unwrap();
+    let trajectory = &battle.trajectories(2, None)[0];
+    assert_eq!(trajectory.end, None);
+    let trajectory = &battle.trajectories(6, None)[0];
+    assert_eq!(
+        trajectory.stops.last().map(|(_, lifetime)| *lifetime),
+        Some(0)
+    );
+    assert_eq!(
+        trajectory.end,
+        trajectory.stops.last().map(|(tile, _)| *tile)
+    );
+}
@@ -0,0 +1,70 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{board::Board, position::Position, wizard::Team, Battle};
+
+///what the wizards on other teams could do on their next turns
+#[derive(Clone, Debug, Default)]
+pub struct Threats {
+    ///the most damage each tile could take, added up over the enemies
+    pub damage: HashMap<Position, usize>,
+    ///every tile an enemy could walk to
+    pub reach: HashSet<Position>,
+}
+
+impl Battle {
+    ///where `team` could be hurt next round, going by each enemy's current mana, spells and the
+    ///tiles they can walk to
+    pub fn threats(&self, team: Team) -> Threats {
+        let mut threats = Threats::default();
+        if self.is_placing() || self.is_over() {
+            return threats;
+        }
+        let enemies = (0..self.wizards.len())
+            .filter(|&w| self.wizards[w].team != team && !self.wizards[w].is_dead());
+        for enemy in enemies {
+            //plays out the enemy's turn on a copy, from each tile they could cast from
+            let mut battle = self.hypothetical_turn(enemy);
+            let reach = battle.valid_move_positions.clone();
+            let wiz = &self.wizards[enemy];
+            let spells = wiz
+                .spells
+                .iter()
+                .filter(|spell| spell.mana_cost() <= wiz.mana)
+                .collect::<Vec<_>>();
+            let mut worst = HashMap::new();
+            for &tile in &reach {
+                battle.wizards[enemy].position = tile;
+                battle.board = Board::new(&battle.wizards, &battle.projectiles);
+                for spell in &spells {
+                    for (tile, damage) in spell.threat(&battle) {
+                        let worst = worst.entry(tile).or_default();
+                        *worst = damage.max(*worst);
+                    }
+                }
+            }
+            for (tile, damage) in worst {
+                *threats.damage.entry(tile).or_default() += damage;
+            }
+            threats.reach.extend(reach);
+        }
+        threats
+    }
+
+    ///the tiles the wizard could walk to if their turn started now
+    pub fn reach(&self, wizard: usize) -> HashSet<Position> {
+        self.hypothetical_turn(wizard).valid_move_positions
+    }
+
+    ///a copy of the battle where it's the wizard's turn and they haven't done anything yet
+    fn hypothetical_turn(&self, wizard: usize) -> Battle {
+        let mut battle = Battle {
+            undo_stack: vec![],
+            ..self.clone()
+        };
+        battle.current_player = wizard;
+        battle.has_moved = false;
+        battle.has_cast = false;
+        battle.update_valid_move_positons();
+        battle
+    }
+}
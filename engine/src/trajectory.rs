@@ -0,0 +1,99 @@
+use super::{
+    log::{DamageSource, LogEvent},
+    position::Position,
+    projectile::ProjectileType,
+    Battle, ProjectileStep,
+};
+
+///where a projectile is headed over the next few turns if nobody gets in its way
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trajectory {
+    pub start: Position,
+    pub projectile_type: ProjectileType,
+    ///every tile it passes through, in order
+    pub path: Vec<Position>,
+    ///where it is at the end of each turn it moves on, with the lifetime it has left by then
+    pub stops: Vec<(Position, usize)>,
+    ///the tile it burns out on or the wizard it hits, if that happens within the turns looked at
+    pub end: Option<Position>,
+}
+
+impl Battle {
+    ///plays the next `turns` turns out on a copy where only one projectile moves at a time and
+    ///nobody else acts, except the current wizard walking to `planned` first if they can
+    pub fn trajectories(&self, turns: usize, planned: Option<Position>) -> Vec<Trajectory> {
+        if self.is_placing() || self.is_over() {
+            return vec![];
+        }
+        let mut base = Battle {
+            log: vec![],
+            actions: vec![],
+            undo_stack: vec![],
+            ..self.clone()
+        };
+        if let Some(tile) = planned.filter(|tile| base.wizard_can_move(*tile)) {
+            base.move_wizard_to(base.current_player, tile);
+        }
+        //the current turn ends first, then the ones after it
+        let turns = std::iter::once(base.current_player)
+            .chain(base.upcoming_turns(turns.saturating_sub(1)))
+            .collect::<Vec<_>>();
+
+        (0..base.projectiles.len())
+            .map(|p| {
+                let mut battle = base.clone();
+                let proj = &battle.projectiles[p];
+                let mut trajectory = Trajectory {
+                    start: proj.position,
+                    projectile_type: proj.projectile_type,
+                    path: vec![],
+                    stops: vec![],
+                    end: None,
+                };
+                let mover = battle.projectile_mover(p);
+                let moves = turns
+                    .iter()
+                    .filter(|&&wizard| Some(wizard) == mover)
+                    .count();
+                for _ in 0..moves {
+                    let mut gone = false;
+                    for _ in 0..battle.projectiles[p].speed {
+                        match battle.step_projectile(p) {
+                            ProjectileStep::Moved => {
+                                trajectory.path.push(battle.projectiles[p].position)
+                            }
+                            ProjectileStep::Blocked => break,
+                            ProjectileStep::Hit => {
+                                gone = true;
+                                break;
+                            }
+                        }
+                    }
+                    if gone {
+                        trajectory.end =
+                            battle.log.iter().rev().find_map(|entry| match entry.event {
+                                LogEvent::WizardDamaged {
+                                    position,
+                                    source: DamageSource::Projectile(_),
+                                    ..
+                                } => Some(position),
+                                _ => None,
+                            });
+                        trajectory.path.extend(trajectory.end);
+                        break;
+                    }
+                    let position = battle.projectiles[p].position;
+                    if !battle.burn_projectile(p) {
+                        trajectory.stops.push((position, 0));
+                        trajectory.end = Some(position);
+                        break;
+                    }
+                    trajectory
+                        .stops
+                        .push((position, battle.projectiles[p].lifetime));
+                }
+                trajectory
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumCount};
+
+use super::{
+    effects::{Effect, Effects},
+    position::Position,
+    spell::Spell,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Wizard {
+    pub team: Team,
+    pub health: usize,
+    pub mana: usize,
+    pub effects: Vec<Effect>,
+    pub position: Position,
+    pub spells: Vec<Spell>,
+    pub rest_streak: usize,
+}
+
+impl Wizard {
+    pub fn has_effect(&self, effect: Effects) -> bool {
+        self.effect(effect).is_some()
+    }
+
+    pub fn effect(&self, effect: Effects) -> Option<&Effect> {
+        self.effects.iter().find(|e| e.kind == effect)
+    }
+
+    ///turns left on the effect, 0 if the wizard doesn't have it
+    pub fn effect_turns(&self, effect: Effects) -> usize {
+        self.effect(effect).map_or(0, |e| e.turns)
+    }
+
+    ///returns the damage actually taken
+    pub fn take_damage(&mut self, damage: usize) -> usize {
+        let damage_taken = self
+            .effects
+            .iter()
+            .fold(damage, |damage, e| e.kind.modify_incoming_damage(damage))
+            .min(self.health);
+        self.health -= damage_taken;
+        damage_taken
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health == 0
+    }
+}
+
+///in free-for-all matches every wizard is on a team of their own
+#[derive(
+    strum_macros::EnumIter,
+    EnumCount,
+    strum_macros::FromRepr,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+pub enum Team {
+    Red,
+    Blue,
+    Green,
+    Yellow,
+    Purple,
+    Orange,
+    Cyan,
+    Pink,
+}
@@ -1,9 +1,40 @@
-use super::{model::{position::Direction, spell::SpellElement}, view::controls::Control};
+use std::net::SocketAddr;
+
+use super::{
+    model::{action::Action, position::Direction, spell::SpellElement},
+    view::controls::Control,
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SpellSelect(SpellSelectMessage),
     Battle(BattleMessage),
+    Replay(ReplayMessage),
+    Save,
+    Load,
+    SaveReplay,
+    LoadReplay,
+    JoinAddress(String),
+    Join,
+    OpenLobby,
+    CloseLobby,
+    JoinGame(SocketAddr),
+    ///connects as a spectator instead of a player
+    Watch,
+    WatchGame(SocketAddr),
+    ///checks for news from the other end of a networked battle
+    NetPoll,
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplayMessage {
+    StepForward,
+    StepBack,
+    JumpToTurn(usize),
+    TogglePlay,
+    Speed(u64),
+    Tick,
+    Close,
 }
 
 #[derive(Debug, Clone)]
@@ -13,11 +44,32 @@ pub enum BattleMessage {
     SpellChoose(usize),
     DirectionSelect(Direction),
     ConfirmAction(Control),
+    EndTurn,
+    Undo,
+    ///what an AI or bot picked, and how many actions had been taken when it started thinking
+    AgentAction(usize, Result<Action, String>),
+    LogEntrySelect(usize),
+    LogDamageOnly(bool),
+    LogMineOnly(bool),
+    ///shades tiles by how much the enemy could hurt them next round
+    ShowThreats(bool),
+    ///marks the tiles the enemy could walk to next round
+    ShowEnemyReach(bool),
 }
 
 #[derive(Debug, Clone)]
 pub enum SpellSelectMessage {
     PointChange(PointChange),
+    SeatToggle(usize),
+    TeamToggle(usize),
+    AddPlayer,
+    RemovePlayer,
+    FreeForAll,
+    ///splits the players into red and blue, alternating by seat
+    TwoTeams,
+    ///cycles how wizards get their starting tiles
+    PlacementToggle,
+    BotCommand(usize, String),
     Confirm,
 }
 
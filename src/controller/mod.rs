@@ -1,35 +1,104 @@
 pub mod message;
-pub mod model;
 pub mod view;
 
+pub use wizard_fight_engine as model;
+
+use std::{
+    collections::HashMap,
+    sync::{mpsc::TryRecvError, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
 use iced::{
+    futures::channel::oneshot,
+    keyboard::{self, Key},
+    time,
     widget::pane_grid::{self, Axis, Configuration},
-    Task,
+    Subscription, Task,
 };
-use message::{BattleMessage, Message, SpellSelectMessage};
+use message::{BattleMessage, Message, ReplayMessage, SpellSelectMessage};
 use model::{
-    position::Direction,
+    action::Action,
+    agent::{self, Agent},
+    forecast::Forecast,
+    lobby::{self, Discovery},
+    map::Placement,
+    net::{Client, ClientMessage, Rejoin, Server, ServerMessage, Session, DEFAULT_PORT},
+    position::{Direction, Position},
+    replay::{Replay, ReplayViewer, REPLAY_PATH},
+    save::SAVE_PATH,
     spell::{SpellInputType, SPELL_POSITION_FILTER},
-    Battle, Model,
+    threat::Threats,
+    trajectory::Trajectory,
+    wizard::Team,
+    Battle, Model, Seat,
 };
+use strum::IntoEnumIterator;
 use view::controls::Control;
 
 pub struct Controller {
     model: Model,
     quit: bool,
-    fullscreen: bool,
     battle_panes: pane_grid::State<BattlePane>,
+    ///the battle panes without the controls, for spectators
+    spectator_panes: pane_grid::State<BattlePane>,
     hovered_tile: (usize, usize),
     control_page: isize,
     selected_tile: Option<(usize, usize)>,
     current_spell_index: Option<usize>,
     current_direction: Option<Direction>,
+    selected_log_entry: Option<usize>,
+    log_filter: LogFilter,
+    overlays: Overlays,
+    ///the threats worked out for the overlays, the team they threaten and the actions the battle
+    ///had taken by then
+    threats: Option<(Vec<Action>, Team, Threats)>,
+    ///the forecast of the spell the controls describe, with that cast and the actions the battle
+    ///had taken by then
+    forecast: Option<(Action, Vec<Action>, Option<Forecast>)>,
+    ///where projectiles are headed, with the tile the current wizard was thinking of walking to
+    ///and the actions the battle had taken by then
+    trajectories: Option<(Option<Position>, Vec<Action>, Vec<Trajectory>)>,
+    status: Option<String>,
+    replay_playing: bool,
+    ///replay actions per second while autoplaying
+    replay_speed: u64,
+    agent_thinking: bool,
+    ///started the first time their seat is up, so bot processes live for the whole battle
+    agents: HashMap<usize, SharedAgent>,
+    ///the other end of a networked battle
+    session: Option<Session>,
+    join_address: String,
+    ///the address and seat last joined, so a dropped connection gets its seat back
+    joined: Option<(String, Rejoin)>,
+    ///the step an action was sent to the host at, agents wait until it comes back
+    sent_step: Option<usize>,
+    ///listens for games on the local network while the lobby is open
+    lobby: Option<Discovery>,
 }
 
 enum BattlePane {
     Battle,
     Info,
     Control,
+    Log,
+}
+
+type SharedAgent = Arc<Mutex<Option<Box<dyn Agent>>>>;
+
+#[derive(Default, Clone, Copy)]
+pub struct LogFilter {
+    pub damage_only: bool,
+    ///only what the wizards played at this screen did
+    pub mine_only: bool,
+}
+
+///what gets drawn over the board on top of the battle itself
+#[derive(Default, Clone, Copy)]
+pub struct Overlays {
+    pub threats: bool,
+    pub enemy_reach: bool,
 }
 
 impl Default for Controller {
@@ -37,11 +106,15 @@ impl Default for Controller {
         Self {
             model: Model::default(),
             quit: false,
-            fullscreen: false,
             battle_panes: pane_grid::State::with_configuration(Configuration::Split {
                 axis: Axis::Vertical,
                 ratio: 3.0 / 4.0,
-                a: Box::new(Configuration::Pane(BattlePane::Battle)),
+                a: Box::new(Configuration::Split {
+                    axis: Axis::Horizontal,
+                    ratio: 3.0 / 4.0,
+                    a: Box::new(Configuration::Pane(BattlePane::Battle)),
+                    b: Box::new(Configuration::Pane(BattlePane::Log)),
+                }),
                 b: Box::new(Configuration::Split {
                     axis: Axis::Horizontal,
                     ratio: 1.0 / 3.0,
@@ -49,11 +122,38 @@ impl Default for Controller {
                     b: Box::new(Configuration::Pane(BattlePane::Control)),
                 }),
             }),
+            spectator_panes: pane_grid::State::with_configuration(Configuration::Split {
+                axis: Axis::Vertical,
+                ratio: 3.0 / 4.0,
+                a: Box::new(Configuration::Split {
+                    axis: Axis::Horizontal,
+                    ratio: 3.0 / 4.0,
+                    a: Box::new(Configuration::Pane(BattlePane::Battle)),
+                    b: Box::new(Configuration::Pane(BattlePane::Log)),
+                }),
+                b: Box::new(Configuration::Pane(BattlePane::Info)),
+            }),
             hovered_tile: (0, 0),
             selected_tile: None,
             control_page: 0,
             current_spell_index: None,
             current_direction: None,
+            selected_log_entry: None,
+            log_filter: LogFilter::default(),
+            overlays: Overlays::default(),
+            threats: None,
+            forecast: None,
+            trajectories: None,
+            status: None,
+            replay_playing: false,
+            replay_speed: 2,
+            agent_thinking: false,
+            agents: HashMap::new(),
+            session: None,
+            join_address: format!("127.0.0.1:{DEFAULT_PORT}"),
+            joined: None,
+            sent_step: None,
+            lobby: None,
         }
     }
 }
@@ -65,20 +165,443 @@ impl Controller {
                 self.update_spell_select(spell_select_message)
             }
             Message::Battle(battle_message) => self.update_battle_message(battle_message),
+            Message::Replay(replay_message) => self.update_replay(replay_message),
+            Message::Save => {
+                self.status = Some(match self.model.save(SAVE_PATH) {
+                    Ok(()) => format!("Saved to {SAVE_PATH}"),
+                    Err(e) => e.to_string(),
+                })
+            }
+            Message::Load => match Model::load(SAVE_PATH) {
+                Ok(model) => {
+                    *self = Self {
+                        model,
+                        status: Some(format!("Loaded {SAVE_PATH}")),
+                        ..Self::default()
+                    }
+                }
+                Err(e) => self.status = Some(e.to_string()),
+            },
+            Message::SaveReplay => {
+                if let Model::Battle(battle) = &self.model {
+                    self.status = Some(match battle.replay().save(REPLAY_PATH) {
+                        Ok(()) => format!("Saved replay to {REPLAY_PATH}"),
+                        Err(e) => e.to_string(),
+                    })
+                }
+            }
+            Message::LoadReplay => match Replay::load(REPLAY_PATH).and_then(ReplayViewer::new) {
+                Ok(viewer) => {
+                    *self = Self {
+                        model: Model::Replay(Box::new(viewer)),
+                        status: Some(format!("Loaded replay {REPLAY_PATH}")),
+                        ..Self::default()
+                    }
+                }
+                Err(e) => self.status = Some(e.to_string()),
+            },
+            Message::JoinAddress(address) => self.join_address = address,
+            Message::Join => self.join(),
+            Message::OpenLobby => match Discovery::listen() {
+                Ok(discovery) => self.lobby = Some(discovery),
+                Err(e) => self.status = Some(format!("couldn't look for games: {e}")),
+            },
+            Message::CloseLobby => self.lobby = None,
+            Message::JoinGame(addr) => {
+                self.join_address = addr.to_string();
+                self.join();
+            }
+            Message::Watch => self.watch(),
+            Message::WatchGame(addr) => {
+                self.join_address = addr.to_string();
+                self.watch();
+            }
+            Message::NetPoll => {}
         };
+        self.poll_session();
+        self.refresh_threats();
+        self.refresh_forecast();
+        self.refresh_trajectories();
         if self.quit {
             iced::exit()
         } else {
-            Task::none()
+            self.start_agent_turn()
+        }
+    }
+
+    ///works the threats out again if an overlay needs them and the battle moved on since, they're
+    ///too slow to do on every redraw
+    fn refresh_threats(&mut self) {
+        let (battle, team) = match &self.model {
+            Model::Battle(battle) => (battle.as_ref(), self.local_team(battle)),
+            //replays are watched from the side whose turn is shown
+            Model::Replay(viewer) => (viewer.battle(), viewer.battle().get_current_wizard().team),
+            Model::SpellSelect(_) => {
+                self.threats = None;
+                return;
+            }
+        };
+        if !self.overlays.threats && !self.overlays.enemy_reach {
+            self.threats = None;
+            return;
+        }
+        if self
+            .threats
+            .as_ref()
+            .is_some_and(|(actions, for_team, _)| actions == battle.actions() && *for_team == team)
+        {
+            return;
+        }
+        let threats = battle.threats(team);
+        self.threats = Some((battle.actions().to_vec(), team, threats));
+    }
+
+    ///works the forecast out again if the spell inputs changed or the battle moved on since, it's
+    ///a whole dry run so the board and the controls share one
+    fn refresh_forecast(&mut self) {
+        let pending = match &self.model {
+            Model::Battle(battle) => self
+                .pending_action(battle, Control::Spell)
+                .filter(|action| matches!(action, Action::Cast(..)))
+                .map(|action| (action, battle)),
+            _ => None,
+        };
+        let Some((action, battle)) = pending else {
+            self.forecast = None;
+            return;
+        };
+        if self
+            .forecast
+            .as_ref()
+            .is_some_and(|(cast, actions, _)| *cast == action && actions == battle.actions())
+        {
+            return;
+        }
+        let forecast = battle.forecast(action).ok();
+        self.forecast = Some((action, battle.actions().to_vec(), forecast));
+    }
+
+    ///works the projectile paths out again if the battle moved on or the current wizard is thinking
+    ///of walking somewhere else, they play several turns out
+    fn refresh_trajectories(&mut self) {
+        let (battle, interactive) = match &self.model {
+            Model::Battle(battle) => (battle.as_ref(), true),
+            Model::Replay(viewer) => (viewer.battle(), false),
+            Model::SpellSelect(_) => {
+                self.trajectories = None;
+                return;
+            }
+        };
+        //homing ones follow the current wizard to the tile they're thinking of walking to
+        let planned = (interactive && self.get_control() == Control::Movement)
+            .then(|| self.hovered_tile.into());
+        if self
+            .trajectories
+            .as_ref()
+            .is_some_and(|(tile, actions, _)| *tile == planned && actions == battle.actions())
+        {
+            return;
+        }
+        let trajectories = battle.trajectories(view::TRAJECTORY_TURNS, planned);
+        self.trajectories = Some((planned, battle.actions().to_vec(), trajectories));
+    }
+
+    ///hands the battle to the seat's agent on a separate thread if it isn't a human's turn
+    fn start_agent_turn(&mut self) -> Task<Message> {
+        let Model::Battle(battle) = &self.model else {
+            return Task::none();
+        };
+        let seat = battle.current_seat().clone();
+        if self.agent_thinking
+            || seat.is_human()
+            || seat.is_remote()
+            || !self.plays(&battle.setup().seats, battle.current_player())
+            || self.sent_step == Some(battle.actions().len())
+            || battle.is_over()
+        {
+            return Task::none();
+        }
+        self.agent_thinking = true;
+        let agent = self
+            .agents
+            .entry(battle.current_player())
+            .or_default()
+            .clone();
+        let battle = battle.clone();
+        let step = battle.actions().len();
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let mut agent = agent.lock().expect("agent threads don't panic");
+            if agent.is_none() {
+                //a bot that can't start gets asked again next turn
+                match seat.agent().expect("humans don't get agents") {
+                    Ok(new) => *agent = Some(new),
+                    Err(e) => {
+                        let _ = sender.send(Err(format!("couldn't start bot: {e}")));
+                        return;
+                    }
+                }
+            }
+            let agent = agent.as_mut().expect("agent was just started");
+            let _ =
+                sender.send(agent::next_action(agent.as_mut(), &battle).map_err(|e| e.to_string()));
+        });
+        Task::perform(receiver, move |result| {
+            Message::Battle(BattleMessage::AgentAction(
+                step,
+                result.unwrap_or(Err("agent stopped".to_owned())),
+            ))
+        })
+    }
+
+    ///whether this instance decides for `player`, which is every player unless networked
+    pub fn plays(&self, seats: &[Seat], player: usize) -> bool {
+        self.session
+            .as_ref()
+            .is_none_or(|session| session.plays(seats, player))
+    }
+
+    fn join(&mut self) {
+        let rejoin = self
+            .joined
+            .as_ref()
+            .filter(|(address, _)| *address == self.join_address)
+            .map(|(_, rejoin)| *rejoin);
+        match Client::connect(self.join_address.as_str(), rejoin) {
+            Ok(client) => {
+                self.session = Some(Session::Join(client));
+                self.lobby = None;
+                self.status = Some(format!("Connected to {}", self.join_address));
+            }
+            Err(e) => self.status = Some(format!("couldn't join {}: {e}", self.join_address)),
+        }
+    }
+
+    fn watch(&mut self) {
+        match Client::watch(self.join_address.as_str(), None) {
+            Ok(client) => {
+                self.session = Some(Session::Join(client));
+                self.lobby = None;
+                self.status = Some(format!("Connected to {}", self.join_address));
+            }
+            Err(e) => self.status = Some(format!("couldn't watch {}: {e}", self.join_address)),
+        }
+    }
+
+    pub fn spectating(&self) -> bool {
+        matches!(&self.session, Some(Session::Join(client)) if client.is_spectating())
+    }
+
+    ///whether the current wizard is waiting on someone at this screen
+    pub fn awaiting_input(&self, battle: &Battle) -> bool {
+        self.sits_here(battle, battle.current_player())
+    }
+
+    ///the team of whoever is at this screen: the current wizard's when they sit here, like in hot
+    ///seat, otherwise the first one played here, or the current wizard's when only watching
+    fn local_team(&self, battle: &Battle) -> Team {
+        let player = match self.sits_here(battle, battle.current_player()) {
+            true => battle.current_player(),
+            false => (0..battle.setup().seats.len())
+                .find(|&player| self.sits_here(battle, player))
+                .unwrap_or(battle.current_player()),
+        };
+        battle.get_wizard(player).team
+    }
+
+    ///whether the wizard is played by someone at this screen rather than a bot or another end
+    pub fn sits_here(&self, battle: &Battle, player: usize) -> bool {
+        let seats = &battle.setup().seats;
+        self.plays(seats, player)
+            && seats
+                .get(player)
+                .is_some_and(|seat| seat.is_human() || seat.is_remote())
+    }
+
+    ///applies the action, or hands it to the host when playing over the network
+    fn submit(&mut self, action: Action) {
+        let Model::Battle(battle) = &mut self.model else {
+            return;
+        };
+        let step = battle.actions().len();
+        let result = match &mut self.session {
+            None => battle.apply(action).map_err(|e| e.to_string()),
+            Some(session) => battle
+                .is_legal(action)
+                .map_err(|e| e.to_string())
+                .and_then(|()| {
+                    self.sent_step = Some(step);
+                    session
+                        .send(ClientMessage::Action { step, action })
+                        .map_err(|e| format!("couldn't reach the host: {e}"))
+                }),
+        };
+        match result {
+            Ok(()) => self.clear_inputs(),
+            Err(e) => self.status = Some(e),
+        }
+    }
+
+    fn clear_inputs(&mut self) {
+        self.selected_tile = None;
+        self.current_spell_index = None;
+        self.current_direction = None;
+    }
+
+    ///applies whatever the host sent since the last poll
+    fn poll_session(&mut self) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        let mut messages = vec![];
+        let disconnected = loop {
+            match session.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        for message in messages {
+            match message {
+                ServerMessage::Welcome { seat, game, token } => {
+                    let rejoin = Rejoin { game, seat, token };
+                    self.joined = Some((self.join_address.clone(), rejoin));
+                    self.status = Some(format!("Joined as player {}", seat + 1));
+                }
+                ServerMessage::Watching { delay, .. } => {
+                    self.status = Some(match delay {
+                        0 => "Watching live".to_owned(),
+                        1 => "Watching 1 turn behind".to_owned(),
+                        n => format!("Watching {n} turns behind"),
+                    })
+                }
+                ServerMessage::Room(room) => self.model = Model::SpellSelect(room),
+                ServerMessage::State(replay) => match replay.battle_at(replay.actions.len()) {
+                    Ok(battle) => {
+                        self.model = Model::Battle(Box::new(battle));
+                        self.sent_step = None;
+                        self.clear_inputs();
+                    }
+                    Err(e) => {
+                        self.status = Some(format!("the host sent an impossible battle: {e}"))
+                    }
+                },
+                ServerMessage::Applied { step, action } => {
+                    let applied = match &mut self.model {
+                        Model::Battle(battle) if battle.actions().len() == step => {
+                            battle.apply(action).is_ok()
+                        }
+                        _ => false,
+                    };
+                    if !applied {
+                        if let Some(session) = &mut self.session {
+                            let _ = session.send(ClientMessage::Resync);
+                        }
+                    }
+                    self.clear_inputs();
+                }
+                ServerMessage::Rejected(reason) => {
+                    self.sent_step = None;
+                    self.status = Some(reason);
+                }
+                ServerMessage::Joined(seat) => {
+                    self.status = Some(format!("Player {} connected", seat + 1))
+                }
+                ServerMessage::Left(seat) => {
+                    self.status = Some(format!("Player {} disconnected", seat + 1))
+                }
+            }
         }
+        if disconnected {
+            self.session = None;
+            self.sent_step = None;
+            self.status =
+                Some("Lost the connection to the host, press Join to reconnect".to_owned());
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let autoplay = match &self.model {
+            Model::Replay(_) if self.replay_playing => {
+                time::every(Duration::from_millis(1000 / self.replay_speed))
+                    .map(|_| Message::Replay(ReplayMessage::Tick))
+            }
+            _ => Subscription::none(),
+        };
+        let shortcuts = keyboard::on_key_press(|key, modifiers| match key.as_ref() {
+            Key::Character("z") if modifiers.command() => {
+                Some(Message::Battle(BattleMessage::Undo))
+            }
+            _ => None,
+        });
+        let net = match &self.session {
+            Some(_) => time::every(Duration::from_millis(50)).map(|_| Message::NetPoll),
+            None => Subscription::none(),
+        };
+        //redraws the list of games as announcements come and go
+        let lobby = match &self.lobby {
+            Some(_) => time::every(lobby::ANNOUNCE_INTERVAL).map(|_| Message::NetPoll),
+            None => Subscription::none(),
+        };
+        Subscription::batch([autoplay, shortcuts, net, lobby])
     }
 
     pub fn update_battle_message(&mut self, message: BattleMessage) {
+        //these only change what's being looked at, so they also work while watching a replay
+        match message {
+            BattleMessage::LogEntrySelect(i) => {
+                self.selected_log_entry = if self.selected_log_entry == Some(i) {
+                    None
+                } else {
+                    Some(i)
+                };
+                return;
+            }
+            BattleMessage::LogDamageOnly(damage_only) => {
+                self.log_filter.damage_only = damage_only;
+                return;
+            }
+            BattleMessage::LogMineOnly(mine_only) => {
+                self.log_filter.mine_only = mine_only;
+                return;
+            }
+            BattleMessage::ShowThreats(threats) => {
+                self.overlays.threats = threats;
+                return;
+            }
+            BattleMessage::ShowEnemyReach(enemy_reach) => {
+                self.overlays.enemy_reach = enemy_reach;
+                return;
+            }
+            BattleMessage::TileSelect(x, y) if matches!(self.model, Model::Replay(_)) => {
+                self.hovered_tile = (x, y);
+                return;
+            }
+            _ => {}
+        }
+
         let control = self.get_control();
-        let Model::Battle(battle) = &mut self.model else {
+        let Model::Battle(battle) = &self.model else {
             return;
         };
+        let awaiting_input = self.awaiting_input(battle);
+        let Model::Battle(battle) = &mut self.model else {
+            unreachable!();
+        };
         match message {
+            BattleMessage::AgentAction(step, result) => {
+                self.agent_thinking = false;
+                //the battle may have been undone or reloaded while the agent was thinking
+                if battle.actions().len() == step {
+                    let action = result.unwrap_or_else(|e| {
+                        self.status = Some(format!("{e}, passing"));
+                        battle.pass_action()
+                    });
+                    self.submit(action);
+                }
+            }
+            BattleMessage::ConfirmAction(_) | BattleMessage::EndTurn | BattleMessage::Undo
+                if !awaiting_input => {}
             BattleMessage::TileSelect(x, y) => {
                 self.hovered_tile = (x, y);
                 if let Some(index) = self.current_spell_index {
@@ -90,7 +613,9 @@ impl Controller {
                         }
                     }
                 }
-                if control == Control::Movement && battle.wizard_can_move((x, y).into()) {
+                if (control == Control::Movement && battle.wizard_can_move((x, y).into()))
+                    || battle.can_place((x, y).into())
+                {
                     self.selected_tile = Some((x, y));
                 }
             }
@@ -121,22 +646,79 @@ impl Controller {
             }
             BattleMessage::DirectionSelect(direction) => self.current_direction = Some(direction),
             BattleMessage::ConfirmAction(control) => {
-                match control {
-                    view::controls::Control::Movement => battle.move_current_wizard_to(
-                        self.selected_tile
-                            .expect("Tile wasn't selected before moving")
-                            .into(),
-                    ),
-                    view::controls::Control::Spell => todo!(),
+                let Model::Battle(battle) = &self.model else {
+                    unreachable!();
                 };
+                let action = self
+                    .pending_action(battle, control)
+                    .expect("Confirmed an action without inputting it");
+                self.submit(action);
+            }
+            BattleMessage::Undo => match &mut self.session {
+                Some(session) => {
+                    if let Err(e) = session.send(ClientMessage::Undo) {
+                        self.status = Some(format!("couldn't reach the host: {e}"));
+                    }
+                }
+                None => {
+                    if battle.undo() {
+                        self.clear_inputs();
+                    }
+                }
+            },
+            BattleMessage::EndTurn => self.submit(Action::EndTurn),
+            BattleMessage::LogEntrySelect(_)
+            | BattleMessage::LogDamageOnly(_)
+            | BattleMessage::LogMineOnly(_)
+            | BattleMessage::ShowThreats(_)
+            | BattleMessage::ShowEnemyReach(_) => unreachable!(),
+        }
+    }
 
-                //TODO: add stuff for ending and starting a new turn
+    pub fn update_replay(&mut self, message: ReplayMessage) {
+        let Model::Replay(viewer) = &mut self.model else {
+            return;
+        };
+        match message {
+            ReplayMessage::StepForward => viewer.step_forward(),
+            ReplayMessage::StepBack => viewer.step_back(),
+            ReplayMessage::JumpToTurn(turn) => viewer.jump_to_turn(turn),
+            ReplayMessage::TogglePlay => self.replay_playing = !self.replay_playing,
+            ReplayMessage::Speed(speed) => self.replay_speed = speed,
+            ReplayMessage::Tick => {
+                viewer.step_forward();
+                if viewer.is_finished() {
+                    self.replay_playing = false;
+                }
             }
+            ReplayMessage::Close => *self = Self::default(),
         }
     }
 
     pub fn update_spell_select(&mut self, message: SpellSelectMessage) {
         let mut confirm = None;
+        let Model::SpellSelect(spell_select) = &self.model else {
+            return;
+        };
+        let editable = match &message {
+            SpellSelectMessage::PointChange(message) => {
+                self.plays(&spell_select.seats, message.player)
+            }
+            SpellSelectMessage::SeatToggle(_)
+            | SpellSelectMessage::BotCommand(..)
+            | SpellSelectMessage::TeamToggle(_)
+            | SpellSelectMessage::AddPlayer
+            | SpellSelectMessage::RemovePlayer
+            | SpellSelectMessage::FreeForAll
+            | SpellSelectMessage::TwoTeams
+            | SpellSelectMessage::PlacementToggle => {
+                !matches!(self.session, Some(Session::Join(_)))
+            }
+            SpellSelectMessage::Confirm => true,
+        };
+        if !editable {
+            return;
+        }
         if let Model::SpellSelect(spell_select) = &mut self.model {
             match message {
                 SpellSelectMessage::PointChange(message) => {
@@ -156,13 +738,85 @@ impl Controller {
                         player.unused += 1;
                     }
                 }
+                SpellSelectMessage::SeatToggle(player) => {
+                    let seat = &mut spell_select.seats[player];
+                    *seat = match seat {
+                        Seat::Human => Seat::Ai,
+                        Seat::Ai => Seat::Process(String::new()),
+                        Seat::Process(_) => Seat::Remote,
+                        Seat::Remote => Seat::Human,
+                    };
+                }
+                SpellSelectMessage::BotCommand(player, command) => {
+                    spell_select.seats[player] = Seat::Process(command)
+                }
+                SpellSelectMessage::TeamToggle(player) => {
+                    //only as many teams as there are players, so the choice stays short
+                    let next =
+                        (spell_select.teams[player] as usize + 1) % spell_select.players.len();
+                    spell_select.teams[player] =
+                        Team::from_repr(next).expect("there are at least as many teams as players");
+                }
+                SpellSelectMessage::AddPlayer => spell_select.add_player(),
+                SpellSelectMessage::RemovePlayer => spell_select.remove_player(),
+                SpellSelectMessage::FreeForAll => spell_select.free_for_all(),
+                SpellSelectMessage::TwoTeams => spell_select.two_teams(),
+                SpellSelectMessage::PlacementToggle => {
+                    spell_select.placement = Placement::iter()
+                        .cycle()
+                        .skip_while(|mode| *mode != spell_select.placement)
+                        .nth(1)
+                        .expect("the modes cycle forever");
+                }
                 SpellSelectMessage::Confirm => {
                     confirm = Some(spell_select.clone());
                 }
             }
         }
-        if let Some(spell_select) = confirm {
-            self.model = Model::Battle(Battle::new(&spell_select));
+        let Some(spell_select) = confirm else {
+            //everyone in the room sees the change
+            match (&self.model, &mut self.session) {
+                (Model::SpellSelect(spell_select), Some(Session::Host(server))) => {
+                    server.set_room(spell_select.clone())
+                }
+                (Model::SpellSelect(spell_select), Some(Session::Join(client))) => {
+                    if let Some(seat) = client.seat() {
+                        let build = spell_select.players[seat].clone();
+                        if let Err(e) = client.send(&ClientMessage::Build(seat, build)) {
+                            self.status = Some(format!("couldn't reach the host: {e}"));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return;
+        };
+        match &self.session {
+            //the battle arrives like it does for everyone else
+            Some(Session::Host(server)) => {
+                if let Err(e) = server.start() {
+                    self.status = Some(e);
+                }
+            }
+            Some(Session::Join(_)) => {}
+            None if spell_select.seats.iter().any(Seat::is_remote) => {
+                match Server::host_room(DEFAULT_PORT, spell_select) {
+                    Ok(server) => {
+                        self.status = Some(match server.announce(lobby::host_name()) {
+                            Ok(()) => {
+                                format!("Hosting on port {}, waiting for players", server.port())
+                            }
+                            Err(e) => format!(
+                                "Hosting on port {}, but couldn't announce it: {e}",
+                                server.port()
+                            ),
+                        });
+                        self.session = Some(Session::Host(server));
+                    }
+                    Err(e) => self.status = Some(format!("couldn't host: {e}")),
+                }
+            }
+            None => self.model = Model::Battle(Box::new(Battle::new(&spell_select))),
         }
     }
 }
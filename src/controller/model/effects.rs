@@ -1,10 +0,0 @@
-#[derive(strum_macros::EnumCount, strum_macros::FromRepr, strum_macros::Display)]
-pub enum Effects{
-    Circulation = 0,
-    Stagnant,
-    AuraOfFire,
-    StoneSkin,
-    Tornado,
-}
-
-
@@ -1,8 +1,10 @@
 use crate::controller::{
     message::BattleMessage,
     model::{
+        action::{Action, SpellTarget},
+        forecast::Forecast,
         position::Direction,
-        spell::{SpellInputType, SPELL_POSITION_FILTER},
+        spell::SpellInputType,
         Battle,
     },
     Controller,
@@ -24,7 +26,8 @@ pub enum Control {
 }
 
 impl Controller {
-    pub fn view_controls(&self, battle: &Battle, control: Control) -> Element<BattleMessage> {
+    #[allow(unstable_name_collisions)] //itertools' intersperse_with
+    pub fn view_controls(&self, battle: &Battle, control: Control) -> Element<'_, BattleMessage> {
         let wizard = battle.get_current_wizard();
         let d_pad = || {
             responsive(move |size| {
@@ -132,8 +135,11 @@ impl Controller {
                         }
                         SpellInputType::Direction(_) => d_pad().into(),
                     };
-                    spell_controls =
-                        spell_controls.push(column![Text::new(format!("{spell:?}")), control_info]);
+                    let mut info = column![Text::new(format!("{spell:?}")), control_info];
+                    if let Some(forecast) = self.spell_forecast() {
+                        info = info.push(view_forecast(battle, forecast));
+                    }
+                    spell_controls = spell_controls.push(info);
                 };
 
                 spell_controls.into()
@@ -141,20 +147,78 @@ impl Controller {
         }
     }
 
-    pub fn controls_inputted(&self, battle: &Battle, control: Control) -> bool {
+    ///the action the current inputs describe, if they're filled in
+    pub fn pending_action(&self, battle: &Battle, control: Control) -> Option<Action> {
+        if battle.is_placing() {
+            return self.selected_tile.map(|pos| Action::Place(pos.into()));
+        }
         match control {
-            Control::Movement => self
-                .selected_tile
-                .is_some_and(|pos| battle.wizard_can_move(pos.into())),
-            Control::Spell => self.current_spell_index.is_some_and(|i| {
-                match battle.get_current_wizard().spells[i].spell_input_type() {
-                    SpellInputType::None(_) => true,
-                    SpellInputType::Position(j, _) => self
-                        .selected_tile
-                        .is_some_and(|tile| SPELL_POSITION_FILTER[*j](battle, tile.into())),
-                    SpellInputType::Direction(_) => self.current_direction.is_some(),
-                }
-            }),
+            Control::Movement => self.selected_tile.map(|pos| Action::Move(pos.into())),
+            Control::Spell => {
+                let i = self.current_spell_index?;
+                let target = match battle.get_current_wizard().spells[i].spell_input_type() {
+                    SpellInputType::None(_) => SpellTarget::None,
+                    SpellInputType::Position(..) => {
+                        SpellTarget::Position(self.selected_tile?.into())
+                    }
+                    SpellInputType::Direction(_) => SpellTarget::Direction(self.current_direction?),
+                };
+                Some(Action::Cast(i, target))
+            }
         }
     }
+
+    ///what the spell the inputs describe would do, if it can be cast
+    pub fn spell_forecast(&self) -> Option<&Forecast> {
+        self.forecast.as_ref()?.2.as_ref()
+    }
+
+    pub fn controls_inputted(&self, battle: &Battle, control: Control) -> bool {
+        self.pending_action(battle, control)
+            .is_some_and(|action| battle.is_legal(action).is_ok())
+    }
+}
+
+fn view_forecast<'a>(battle: &Battle, forecast: &Forecast) -> Element<'a, BattleMessage> {
+    let mut lines = vec![];
+    for wiz in &forecast.wizards {
+        let name = battle.wizard_name(wiz.wizard);
+        if wiz.health.0 != wiz.health.1 {
+            lines.push(format!(
+                "{name}: {} → {} health",
+                wiz.health.0, wiz.health.1
+            ));
+        }
+        if wiz.mana.0 != wiz.mana.1 {
+            lines.push(format!("{name}: {} → {} mana", wiz.mana.0, wiz.mana.1));
+        }
+        if wiz.drained > 0 {
+            lines.push(format!("{name} is drained of {} mana", wiz.drained));
+        }
+        for effect in &wiz.effects {
+            lines.push(format!(
+                "{name} gets {} for {} turns",
+                effect.kind, effect.turns
+            ));
+        }
+    }
+    for proj in &forecast.projectiles {
+        let (x, y) = (proj.position.x, proj.position.y);
+        lines.push(match proj.damage {
+            (_, None) => format!("{} at ({x}, {y}) is destroyed", proj.projectile_type),
+            (before, Some(after)) => format!(
+                "{} at ({x}, {y}): {before} → {after} damage",
+                proj.projectile_type
+            ),
+        });
+    }
+    if lines.is_empty() {
+        lines.push("No effect".to_owned());
+    }
+    Column::with_children(
+        lines
+            .into_iter()
+            .map(|line| Text::new(line).size(12).into()),
+    )
+    .into()
 }
@@ -0,0 +1,66 @@
+use iced::{
+    widget::{button, column, container, row, text_input, Column, Space, Text},
+    Alignment, Element, Length,
+};
+
+use crate::controller::{message::Message, model::lobby::Discovery, Controller};
+
+impl Controller {
+    pub fn view_lobby(&self, discovery: &Discovery) -> Element<'_, Message> {
+        let games = discovery.games();
+        let list: Element<Message> = if games.is_empty() {
+            Text::new("Looking for games on your network...").into()
+        } else {
+            Column::with_children(games.into_iter().map(|game| {
+                let announcement = game.announcement;
+                let seats = match announcement.free_seats {
+                    0 => "full".to_owned(),
+                    1 => "1 free seat".to_owned(),
+                    n => format!("{n} free seats"),
+                };
+                row![
+                    Text::new(announcement.name).width(Length::FillPortion(2)),
+                    Text::new(announcement.map.to_string()).width(Length::FillPortion(1)),
+                    Text::new(if announcement.started {
+                        format!("{seats}, in progress")
+                    } else {
+                        seats
+                    })
+                    .width(Length::FillPortion(2)),
+                    Text::new(game.addr.to_string()).width(Length::FillPortion(2)),
+                    button("Join").on_press_maybe(
+                        (announcement.free_seats > 0).then_some(Message::JoinGame(game.addr))
+                    ),
+                    button("Watch").on_press(Message::WatchGame(game.addr)),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10.0)
+                .into()
+            }))
+            .spacing(5.0)
+            .into()
+        };
+
+        container(column![
+            Text::new("Games on your network:"),
+            Space::with_height(10.0),
+            list,
+            Space::with_height(Length::Fill),
+            row![
+                text_input("host:port", &self.join_address)
+                    .on_input(Message::JoinAddress)
+                    .width(Length::Fixed(200.0)),
+                button("Join").on_press(Message::Join),
+                button("Watch").on_press(Message::Watch),
+                Space::with_width(Length::Fill),
+                button("Close").on_press(Message::CloseLobby),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10.0),
+        ])
+        .padding(20.0)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
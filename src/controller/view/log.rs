@@ -0,0 +1,74 @@
+use iced::{
+    widget::{button, checkbox, column, container, row, scrollable, Column, Space, Text},
+    Background, Element, Length,
+};
+
+use crate::controller::{
+    message::BattleMessage,
+    model::{position::Position, Battle},
+    Controller,
+};
+
+use super::{GREY, WIZARD_COLORS};
+
+impl Controller {
+    pub fn view_battle_log(&self, battle: &Battle) -> Element<'_, BattleMessage> {
+        let filters = row![
+            checkbox("Damage only", self.log_filter.damage_only)
+                .on_toggle(BattleMessage::LogDamageOnly),
+            Space::with_width(10.0),
+            checkbox("My actions only", self.log_filter.mine_only)
+                .on_toggle(BattleMessage::LogMineOnly),
+        ];
+
+        let entries = battle
+            .log()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.log_filter.damage_only || entry.event.is_damage())
+            .filter(|(_, entry)| !self.log_filter.mine_only || self.sits_here(battle, entry.actor))
+            .map(|(i, entry)| -> Element<BattleMessage> {
+                let color = WIZARD_COLORS[battle.get_wizard(entry.actor).team as usize];
+                let selected = self.selected_log_entry == Some(i);
+                button(
+                    Text::new(format!(
+                        "[{}] {}",
+                        entry.turn + 1,
+                        entry.event.describe(battle)
+                    ))
+                    .color(color),
+                )
+                .on_press(BattleMessage::LogEntrySelect(i))
+                .padding(2)
+                .width(Length::Fill)
+                .style(move |theme, status| {
+                    let mut b = button::text(theme, status);
+                    if selected {
+                        b.background = Some(Background::Color(GREY));
+                    }
+                    b
+                })
+                .into()
+            });
+
+        container(column![
+            Text::new("log:"),
+            filters,
+            Space::with_height(5.0),
+            scrollable(Column::with_children(entries).width(Length::Fill))
+                .anchor_bottom()
+                .height(Length::Fill),
+        ])
+        .padding(10.0)
+        .align_top(Length::Fill)
+        .align_left(Length::Fill)
+        .into()
+    }
+
+    pub fn log_highlighted_tiles(&self, battle: &Battle) -> Vec<Position> {
+        self.selected_log_entry
+            .and_then(|i| battle.log().get(i))
+            .map(|entry| entry.event.tiles())
+            .unwrap_or_default()
+    }
+}
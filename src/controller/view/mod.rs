@@ -2,8 +2,8 @@ use controls::Control;
 use iced::{
     alignment::Horizontal,
     widget::{
-        button, column, container, pane_grid, responsive, row, stack, text::LineHeight, Button,
-        Column, Container, PaneGrid, Row, Space, Text,
+        button, checkbox, column, container, pane_grid, responsive, row, stack, text::LineHeight,
+        text_input, Button, Column, Container, PaneGrid, Row, Space, Text,
     },
     Alignment, Background, Border, Color, Element,
     Length::{self, Fill, FillPortion},
@@ -14,74 +14,193 @@ use strum::{EnumCount, IntoEnumIterator};
 
 use crate::{
     controller::{message::Message, Controller},
-    helper::from_rgb8,
+    helper::{from_rgb8, mix},
 };
 
 use super::{
     message::{BattleMessage, PointChange, SpellSelectMessage},
     model::{
         board,
-        effects::Effects,
+        forecast::Forecast,
+        net::Session,
+        position::Position,
         spell::{SpellElement, SpellInputType, SPELL_POSITION_FILTER},
-        wizard::WIZARD_COLORS,
-        Battle, Model, SpellSelect,
+        wizard::Team,
+        Battle, Model, Seat, SpellSelect,
     },
 };
 
 pub mod controls;
+pub mod lobby;
+pub mod log;
+pub mod replay;
 
 const GREY: Color = from_rgb8(50, 50, 50);
 const YELLOW: Color = from_rgb8(200, 200, 0);
 const GREEN: Color = from_rgb8(0, 200, 0);
 const RED: Color = from_rgb8(200, 0, 0);
 const ORANGE: Color = from_rgb8(255, 140, 0);
+const CYAN: Color = from_rgb8(0, 200, 255);
+const PURPLE: Color = from_rgb8(170, 0, 255);
+const PROJECTILE_BLUE: Color = from_rgb8(32, 102, 219);
+
+pub const WIZARD_COLORS: [Color; Team::COUNT] = [
+    from_rgb8(255, 0, 0),
+    from_rgb8(0, 0, 255),
+    from_rgb8(0, 200, 0),
+    from_rgb8(230, 230, 0),
+    from_rgb8(160, 0, 220),
+    from_rgb8(255, 140, 0),
+    from_rgb8(0, 220, 220),
+    from_rgb8(255, 120, 200),
+];
+
+///how many turns ahead the info pane lists
+const UPCOMING_TURNS: usize = 6;
+///how many turns ahead projectile paths are drawn
+pub const TRAJECTORY_TURNS: usize = 4;
 
 impl Controller {
-    pub fn view(&self) -> Element<Message> {
-        match &self.model {
-            Model::Battle(battle) => self.view_battle(battle).map(Message::Battle),
-            Model::SpellSelect(spell_select) => {
-                Controller::view_spell_select(spell_select).map(Message::SpellSelect)
-            }
-        }
+    pub fn view(&self) -> Element<'_, Message> {
+        let screen = match (&self.model, &self.lobby) {
+            (_, Some(discovery)) => self.view_lobby(discovery),
+            (Model::Battle(battle), None) => self.view_battle(battle),
+            (Model::SpellSelect(spell_select), None) => self
+                .view_spell_select(spell_select)
+                .map(Message::SpellSelect),
+            (Model::Replay(viewer), None) => self.view_battle(viewer.battle()),
+        };
+        column![self.view_menu_bar(), screen].into()
+    }
+
+    fn view_menu_bar(&self) -> Element<'_, Message> {
+        row![
+            button("Save").on_press(Message::Save),
+            button("Load").on_press(Message::Load),
+            button("Save Replay").on_press_maybe(
+                matches!(self.model, Model::Battle(_)).then_some(Message::SaveReplay)
+            ),
+            button("Load Replay").on_press(Message::LoadReplay),
+            button("Lobby").on_press_maybe(self.lobby.is_none().then_some(Message::OpenLobby)),
+            text_input("host:port", &self.join_address)
+                .on_input(Message::JoinAddress)
+                .width(Length::Fixed(200.0)),
+            button("Join").on_press_maybe(self.session.is_none().then_some(Message::Join)),
+            button("Watch").on_press_maybe(self.session.is_none().then_some(Message::Watch)),
+            Text::new(self.status.clone().unwrap_or_default()),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10.0)
+        .padding([5.0, 10.0])
+        .height(Length::Shrink)
+        .into()
     }
 
     fn view_board<'a>(&'a self, battle: &'a Battle) -> Element<'a, BattleMessage> {
+        let log_highlighted = self.log_highlighted_tiles(battle);
+        let interactive = matches!(self.model, Model::Battle(_));
+        //only the wizard picking gets to see their zone
+        let placement_tiles = match interactive && self.awaiting_input(battle) {
+            true => battle.placement_tiles(),
+            false => vec![],
+        };
+        //what the configured spell would do, marked on the tiles it touches
+        let forecast_labels = match interactive && self.get_control() == Control::Spell {
+            true => self
+                .spell_forecast()
+                .map(forecast_labels)
+                .unwrap_or_default(),
+            false => vec![],
+        };
+        let threats = self.threats.as_ref().map(|(_, _, threats)| threats);
+        let threat_damage = threats.filter(|_| self.overlays.threats).map(|t| &t.damage);
+        let enemy_reach = threats
+            .filter(|_| self.overlays.enemy_reach)
+            .map(|t| &t.reach);
+        let trajectories = self
+            .trajectories
+            .as_ref()
+            .map(|(_, _, trajectories)| trajectories.as_slice())
+            .unwrap_or_default();
         let cell = move |x, y| {
+            let tile: Position = (x, y).into();
+            let log_highlight = log_highlighted.contains(&tile);
+            let ghost = trajectories.iter().any(|t| t.path.contains(&tile));
+            let expires = trajectories.iter().any(|t| t.end == Some(tile));
+            //lifetime countdowns where projectiles stop at the end of a turn
+            let badge = trajectories
+                .iter()
+                .flat_map(|t| &t.stops)
+                .find(|(stop, _)| *stop == tile)
+                .map(|(_, lifetime)| lifetime.to_string());
+            let threat = threat_damage
+                .and_then(|damage| damage.get(&(x, y).into()))
+                .copied()
+                .unwrap_or(0);
+            let reachable = enemy_reach.is_some_and(|reach| reach.contains(&(x, y).into()));
+            let label = forecast_labels
+                .iter()
+                .find(|(tile, _)| *tile == (x, y).into())
+                .map(|(_, label)| label.clone())
+                .or(badge)
+                .unwrap_or_default();
+            let placeable = placement_tiles.contains(&(x, y).into());
             let value = battle.get_entity_at((x, y).into());
             let color = match value {
                 Some(ent) => match ent {
                     board::Entity::Wizard(w) => WIZARD_COLORS[battle.get_wizard(w).team as usize],
-                    board::Entity::Projectile(_p) => from_rgb8(32, 102, 219), //temporary
+                    board::Entity::Projectile(_p) => PROJECTILE_BLUE,
                 },
                 None => GREY,
             };
-            button("")
-                .style(move |_, _| button::Style {
-                    background: Some(Background::Color(color)),
-                    border: {
-                        let mut b = Border::default().rounded(2.0);
-                        match (x, y) {
-                            val if val == self.hovered_tile => b = b.color(YELLOW).width(2.0),
-                            val if self.selected_tile.is_some_and(|tile| val == tile)
-                                && (self.current_spell_index.is_some_and(|i| {
-                                    battle.get_current_wizard().spells[i]
-                                        .spell_input_type()
-                                        .is_position()
-                                }) || self.get_control() == Control::Movement) =>
-                            {
-                                b = b.color(ORANGE).width(2.0)
-                            }
-                            val if (self.get_control() == Control::Spell
-                                && self.current_spell_index.is_some_and(|i| {
-                                    match battle.get_current_wizard().spells[i].spell_input_type() {
-                                        SpellInputType::None(damage_filter) => {
-                                            damage_filter(battle, (val.0, val.1).into())
-                                        }
-                                        SpellInputType::Position(
-                                            position_filter,
-                                            damage_filter,
-                                        ) => self.selected_tile.is_some_and(|p1| {
+            let color = match ghost {
+                true => mix(color, PROJECTILE_BLUE, 0.35),
+                false => color,
+            };
+            //a full health bar's worth of damage gets the darkest shade
+            let color = mix(color, RED, threat.min(100) as f32 / 125.0);
+            let color = match expires {
+                true => mix(color, ORANGE, 0.5),
+                false => color,
+            };
+            button(
+                Text::new(label)
+                    .size(10)
+                    .color(Color::WHITE)
+                    .align_x(Alignment::Center)
+                    .align_y(Alignment::Center)
+                    .width(Fill)
+                    .height(Fill),
+            )
+            .padding(0)
+            .style(move |_, _| button::Style {
+                background: Some(Background::Color(color)),
+                border: {
+                    let mut b = Border::default().rounded(2.0);
+                    match (x, y) {
+                        val if val == self.hovered_tile => b = b.color(YELLOW).width(2.0),
+                        val if interactive
+                            && self.selected_tile.is_some_and(|tile| val == tile)
+                            && (self.current_spell_index.is_some_and(|i| {
+                                battle.get_current_wizard().spells[i]
+                                    .spell_input_type()
+                                    .is_position()
+                            }) || self.get_control() == Control::Movement
+                                || battle.is_placing()) =>
+                        {
+                            b = b.color(ORANGE).width(2.0)
+                        }
+                        _ if log_highlight => b = b.color(CYAN).width(2.0),
+                        _ if !interactive => {}
+                        _ if placeable => b = b.color(GREEN).width(2.0),
+                        val if (self.get_control() == Control::Spell
+                            && self.current_spell_index.is_some_and(|i| {
+                                match battle.get_current_wizard().spells[i].spell_input_type() {
+                                    SpellInputType::None(damage_filter) => {
+                                        damage_filter(battle, (val.0, val.1).into())
+                                    }
+                                    SpellInputType::Position(position_filter, damage_filter) => {
+                                        self.selected_tile.is_some_and(|p1| {
                                             SPELL_POSITION_FILTER[*position_filter](
                                                 battle,
                                                 p1.into(),
@@ -90,44 +209,47 @@ impl Controller {
                                                 p1.into(),
                                                 (val.0, val.1).into(),
                                             )
-                                        }),
-                                        SpellInputType::Direction(damage_filter) => {
-                                            self.current_direction.is_some_and(|dir| {
-                                                damage_filter(battle, dir, (val.0, val.1).into())
-                                            })
-                                        }
+                                        })
+                                    }
+                                    SpellInputType::Direction(damage_filter) => {
+                                        self.current_direction.is_some_and(|dir| {
+                                            damage_filter(battle, dir, (val.0, val.1).into())
+                                        })
                                     }
-                                })) =>
-                            {
-                                b = b.color(RED).width(2.0)
-                            }
-                            val if (self.current_spell_index.is_some_and(|i| {
-                                if let super::model::spell::SpellInputType::Position(j, _) =
-                                    battle.get_current_wizard().spells[i].spell_input_type()
-                                {
-                                    SPELL_POSITION_FILTER[*j](battle, (val.0, val.1).into())
-                                } else {
-                                    false
                                 }
-                            }) && self.get_control() == Control::Spell)
-                                || (self.get_control() == Control::Movement
-                                    && battle.wizard_can_move((val.0, val.1).into())) =>
+                            })) =>
+                        {
+                            b = b.color(RED).width(2.0)
+                        }
+                        val if (self.current_spell_index.is_some_and(|i| {
+                            if let super::model::spell::SpellInputType::Position(j, _) =
+                                battle.get_current_wizard().spells[i].spell_input_type()
                             {
-                                b = b.color(GREEN).width(2.0)
+                                SPELL_POSITION_FILTER[*j](battle, (val.0, val.1).into())
+                            } else {
+                                false
                             }
-                            _ => {}
+                        }) && self.get_control() == Control::Spell)
+                            || (self.get_control() == Control::Movement
+                                && battle.wizard_can_move((val.0, val.1).into())) =>
+                        {
+                            b = b.color(GREEN).width(2.0)
                         }
-                        b
-                    },
-                    ..Default::default()
-                })
-                .width(Length::Fill)
-                .height(Fill)
-                .on_press(BattleMessage::TileSelect(x, y))
-                .into()
+                        _ if reachable => b = b.color(PURPLE).width(2.0),
+                        _ => {}
+                    }
+                    b
+                },
+                ..Default::default()
+            })
+            .width(Length::Fill)
+            .height(Fill)
+            .on_press(BattleMessage::TileSelect(x, y))
+            .into()
         };
 
         let board_gen = move || {
+            let cell = &cell;
             container(
                 Column::with_children((0..board::HEIGHT).map(|y| {
                     Row::with_children((0..board::WIDTH).map(|x| cell(x, y)))
@@ -160,7 +282,7 @@ impl Controller {
         .into()
     }
 
-    fn view_battle_info(&self, battle: &Battle) -> Element<BattleMessage> {
+    fn view_battle_info(&self, battle: &Battle) -> Element<'_, BattleMessage> {
         let mut info = Column::with_children([
             Text::new("info:").into(),
             Text::new(format!(
@@ -169,26 +291,47 @@ impl Controller {
             ))
             .into(),
         ]);
+        info = info.push(row![
+            checkbox("Threats", self.overlays.threats).on_toggle(BattleMessage::ShowThreats),
+            Space::with_width(10.0),
+            checkbox("Enemy reach", self.overlays.enemy_reach)
+                .on_toggle(BattleMessage::ShowEnemyReach),
+        ]);
+        if let Some(team) = battle.winner() {
+            info = info.push(Text::new(format!("{team} wins!")).color(YELLOW));
+        }
+        let upcoming = battle.upcoming_turns(UPCOMING_TURNS);
+        if !upcoming.is_empty() && !battle.is_over() {
+            info = info.push(
+                Row::with_children(std::iter::once(Text::new("Up next:").into()).chain(
+                    upcoming.into_iter().map(|wizard| {
+                        Text::new(battle.wizard_name(wizard))
+                            .color(WIZARD_COLORS[battle.get_wizard(wizard).team as usize])
+                            .into()
+                    }),
+                ))
+                .spacing(10.0)
+                .wrap(),
+            );
+        }
         if let Some(entity) = battle.get_entity_at(self.hovered_tile.into()) {
             info = info.extend(match entity {
                 board::Entity::Wizard(w) => {
                     let wiz = battle.get_wizard(w);
                     [
-                        Text::new(format!("Team: {}", wiz.team)).into(),
+                        Text::new(battle.wizard_name(w)).into(),
                         Text::new(format!("Health: {}", wiz.health)).into(),
                         Text::new(format!("Mana: {}", wiz.mana)).into(),
                         Text::new(format!(
                             "Effects: {:?}",
                             wiz.effects
                                 .iter()
-                                .enumerate()
-                                .filter(|(_, i)| { **i != 0 })
-                                .map(|(i, time)| {
+                                .map(|effect| {
                                     format!(
-                                        "{} for {} turns",
-                                        Effects::from_repr(i)
-                                            .expect("effects cannot be longer than Effects"),
-                                        time
+                                        "{} for {} turns from {}",
+                                        effect.kind,
+                                        effect.turns,
+                                        battle.wizard_name(effect.source)
                                     )
                                 })
                                 .collect_vec()
@@ -197,7 +340,31 @@ impl Controller {
                         Text::new(format!("Spells: {:?}", wiz.spells)).into(),
                     ]
                 }
-                board::Entity::Projectile(_p) => todo!(),
+                board::Entity::Projectile(p) => {
+                    let proj = battle.get_projectile(p);
+                    [
+                        Text::new(format!("Projectile: {}", proj.projectile_type)).into(),
+                        Text::new(format!("Owner: {}", proj.owner)).into(),
+                        Text::new(format!("Damage: {}", proj.damage)).into(),
+                        Text::new(format!(
+                            "Speed: {} {:?}{}",
+                            proj.speed,
+                            proj.direction,
+                            if proj.guiding { ", tracking" } else { "" }
+                        ))
+                        .into(),
+                        Text::new(format!(
+                            "Lifetime: {} turns, {}",
+                            proj.lifetime,
+                            if proj.passable {
+                                "passable"
+                            } else {
+                                "impassable"
+                            }
+                        ))
+                        .into(),
+                    ]
+                }
             });
         }
         container(info.padding(10.0))
@@ -214,7 +381,30 @@ impl Controller {
             .expect("its in a cycle and should never return None")
     }
 
-    fn view_battle_controls(&self, battle: &Battle) -> Element<BattleMessage> {
+    fn view_battle_controls(&self, battle: &Battle) -> Element<'_, BattleMessage> {
+        if !self.awaiting_input(battle) && !battle.is_over() {
+            let name = battle.wizard_name(battle.current_player());
+            return container(Text::new(match battle.current_seat() {
+                Seat::Ai => format!("{name} (AI) is thinking..."),
+                Seat::Process(_) => format!("{name} (bot) is thinking..."),
+                Seat::Human | Seat::Remote => format!("Waiting for the {name}..."),
+            }))
+            .center(Length::Fill)
+            .into();
+        }
+        if battle.is_placing() {
+            let name = battle.wizard_name(battle.current_player());
+            return container(column![
+                Text::new(format!("Pick a starting tile for the {name}")),
+                Space::with_height(10.0),
+                Button::new("Confirm").on_press_maybe(
+                    self.controls_inputted(battle, Control::Movement)
+                        .then_some(BattleMessage::ConfirmAction(Control::Movement))
+                ),
+            ])
+            .center(Length::Fill)
+            .into();
+        }
         let controls = self.get_control();
         let menu_bar: Row<'_, BattleMessage> = Row::with_children([
             button("<")
@@ -239,6 +429,17 @@ impl Controller {
                 container(control_view)
                     .width(Length::Fill)
                     .height(Length::Fill),
+                container(column![
+                    row![
+                        Button::new("End Turn").on_press(BattleMessage::EndTurn),
+                        Space::with_width(10.0),
+                        Button::new("Undo")
+                            .on_press_maybe(battle.can_undo().then_some(BattleMessage::Undo)),
+                    ],
+                    Space::new(Length::Shrink, Length::Fixed(10.0))
+                ])
+                .align_bottom(Length::Fill)
+                .align_left(Length::Fill),
                 if self.controls_inputted(battle, controls) {
                     container(column![
                         row![
@@ -261,13 +462,30 @@ impl Controller {
         .into()
     }
 
-    pub fn view_battle<'a>(&'a self, battle: &'a Battle) -> Element<'a, BattleMessage> {
+    ///also used for replays, where the controls pane is swapped for playback controls, and for
+    ///spectators, who don't get one
+    pub fn view_battle<'a>(&'a self, battle: &'a Battle) -> Element<'a, Message> {
+        let panes = if self.spectating() {
+            &self.spectator_panes
+        } else {
+            &self.battle_panes
+        };
         container(
-            PaneGrid::new(&self.battle_panes, |_pane, pane_type, _focus| {
+            PaneGrid::new(panes, |_pane, pane_type, _focus| {
                 let content = match pane_type {
-                    super::BattlePane::Battle => container(self.view_board(battle)),
-                    super::BattlePane::Info => container(self.view_battle_info(battle)),
-                    super::BattlePane::Control => container(self.view_battle_controls(battle)),
+                    super::BattlePane::Battle => {
+                        container(self.view_board(battle).map(Message::Battle))
+                    }
+                    super::BattlePane::Info => {
+                        container(self.view_battle_info(battle).map(Message::Battle))
+                    }
+                    super::BattlePane::Control => match &self.model {
+                        Model::Replay(viewer) => container(self.view_replay_controls(viewer)),
+                        _ => container(self.view_battle_controls(battle).map(Message::Battle)),
+                    },
+                    super::BattlePane::Log => {
+                        container(self.view_battle_log(battle).map(Message::Battle))
+                    }
                 };
 
                 pane_grid::Content::new(
@@ -295,7 +513,12 @@ impl Controller {
         .into()
     }
 
-    pub fn view_spell_select(spell_select: &SpellSelect) -> Element<SpellSelectMessage> {
+    #[allow(unstable_name_collisions)] //itertools' intersperse_with
+    pub fn view_spell_select<'a>(
+        &'a self,
+        spell_select: &'a SpellSelect,
+    ) -> Element<'a, SpellSelectMessage> {
+        let joined = matches!(self.session, Some(Session::Join(_)));
         stack([
             Row::from_vec(
                 spell_select
@@ -303,6 +526,7 @@ impl Controller {
                     .iter()
                     .enumerate()
                     .map(|(i, spell_choice)| {
+                        let editable = self.plays(&spell_select.seats, i);
                         let cell = |text: String| {
                             Container::new(Text::new(text)).center(Length::Fixed(30.0))
                         };
@@ -313,20 +537,20 @@ impl Controller {
                                     container(Text::new(text).width(Length::Fixed(70.0)))
                                         .center_y(Length::Fixed(30.0)),
                                 )
-                                .push(Button::new("-").on_press(SpellSelectMessage::PointChange(
-                                    PointChange {
+                                .push(Button::new("-").on_press_maybe(editable.then_some(
+                                    SpellSelectMessage::PointChange(PointChange {
                                         player: i,
                                         increment: false,
                                         element,
-                                    },
+                                    }),
                                 )))
                                 .push(cell(num.to_string()))
-                                .push(Button::new("+").on_press(SpellSelectMessage::PointChange(
-                                    PointChange {
+                                .push(Button::new("+").on_press_maybe(editable.then_some(
+                                    SpellSelectMessage::PointChange(PointChange {
                                         player: i,
                                         increment: true,
                                         element,
-                                    },
+                                    }),
                                 )))
                         };
                         container(column![
@@ -335,6 +559,43 @@ impl Controller {
                                     .line_height(LineHeight::Relative(2.0))
                             )
                             .center_x(Length::Fill),
+                            container(
+                                Button::new(
+                                    Text::new(spell_select.teams[i].to_string())
+                                        .color(WIZARD_COLORS[spell_select.teams[i] as usize])
+                                )
+                                .style(button::secondary)
+                                .on_press_maybe(
+                                    (!joined).then_some(SpellSelectMessage::TeamToggle(i))
+                                )
+                            )
+                            .padding(5)
+                            .center_x(Length::Fill),
+                            container(
+                                Button::new(match spell_select.seats[i] {
+                                    Seat::Human => "Human",
+                                    Seat::Ai => "AI",
+                                    Seat::Process(_) => "Bot",
+                                    Seat::Remote => "Remote",
+                                })
+                                .on_press_maybe(
+                                    (!joined).then_some(SpellSelectMessage::SeatToggle(i))
+                                )
+                            )
+                            .center_x(Length::Fill),
+                            if let Seat::Process(command) = &spell_select.seats[i] {
+                                container(
+                                    text_input("bot command", command)
+                                        .on_input_maybe((!joined).then_some(move |command| {
+                                            SpellSelectMessage::BotCommand(i, command)
+                                        }))
+                                        .width(Length::Fill),
+                                )
+                                .padding(5)
+                                .center_x(Length::Fill)
+                            } else {
+                                container("")
+                            },
                             container(
                                 container(
                                     Column::new()
@@ -390,14 +651,51 @@ impl Controller {
             .width(Length::Fill)
             .height(Length::Fill)
             .into(),
-            if spell_select
-                .players
-                .iter()
-                .all(|spell_choice| spell_choice.unused == 0)
+            if joined {
+                "".into()
+            } else {
+                container(
+                    row![
+                        Space::new(Length::Fixed(10.0), Length::Shrink),
+                        Text::new(format!("{} players", spell_select.players.len())),
+                        Button::new("-").on_press(SpellSelectMessage::RemovePlayer),
+                        Button::new("+").on_press(SpellSelectMessage::AddPlayer),
+                        Button::new("Free for all").on_press(SpellSelectMessage::FreeForAll),
+                        Button::new("Two teams").on_press(SpellSelectMessage::TwoTeams),
+                        Button::new(Text::new(format!("{} placement", spell_select.placement)))
+                            .on_press(SpellSelectMessage::PlacementToggle),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10.0)
+                    .padding([10.0, 0.0]),
+                )
+                .align_bottom(Length::Fill)
+                .align_left(Length::Fill)
+                .into()
+            },
+            if !joined
+                && spell_select.is_playable()
+                && spell_select
+                    .players
+                    .iter()
+                    .zip(&spell_select.seats)
+                    //remote players finish their builds in the room
+                    .all(|(spell_choice, seat)| {
+                        spell_choice.unused == 0 || (seat.is_remote() && self.session.is_none())
+                    })
+                && spell_select.seats.iter().all(|seat| match seat {
+                    Seat::Process(command) => !command.trim().is_empty(),
+                    _ => true,
+                })
             {
+                let label = match &self.session {
+                    Some(_) => "Start",
+                    None if spell_select.seats.iter().any(Seat::is_remote) => "Host",
+                    None => "Confirm",
+                };
                 container(column![
                     row![
-                        Button::new("Confirm").on_press(SpellSelectMessage::Confirm),
+                        Button::new(label).on_press(SpellSelectMessage::Confirm),
                         Space::new(Length::Fixed(10.0), Length::Shrink)
                     ],
                     Space::new(Length::Shrink, Length::Fixed(10.0))
@@ -412,3 +710,28 @@ impl Controller {
         .into()
     }
 }
+
+///short notes for the board on what a forecast does to each tile
+fn forecast_labels(forecast: &Forecast) -> Vec<(Position, String)> {
+    let wizards = forecast.wizards.iter().map(|wiz| {
+        let mut label = match wiz.health.0.saturating_sub(wiz.health.1) {
+            0 => String::new(),
+            damage => format!("-{damage}"),
+        };
+        if wiz.drained > 0 {
+            label += &format!(" -{}mp", wiz.drained);
+        }
+        for effect in &wiz.effects {
+            label += &format!(" {}", effect.kind.to_string().chars().take(3).join(""));
+        }
+        (wiz.position, label.trim().to_owned())
+    });
+    let projectiles = forecast.projectiles.iter().map(|proj| {
+        let label = match proj.damage {
+            (_, None) => "x".to_owned(),
+            (before, Some(after)) => format!("-{}", before - after),
+        };
+        (proj.position, label)
+    });
+    wizards.chain(projectiles).collect()
+}
@@ -0,0 +1,70 @@
+use iced::{
+    widget::{button, column, container, row, slider, Space, Text},
+    Alignment, Element, Length,
+};
+
+use crate::controller::{
+    message::{Message, ReplayMessage},
+    model::replay::ReplayViewer,
+    Controller,
+};
+
+const SPEEDS: [u64; 4] = [1, 2, 4, 8];
+
+impl Controller {
+    pub fn view_replay_controls(&self, viewer: &ReplayViewer) -> Element<'_, Message> {
+        let replay = viewer.replay();
+        let battle = viewer.battle();
+        let turns = replay.turn_count();
+
+        let steps = row![
+            button("<").on_press_maybe(
+                (viewer.step() > 0).then_some(Message::Replay(ReplayMessage::StepBack))
+            ),
+            button(if self.replay_playing { "Pause" } else { "Play" })
+                .on_press(Message::Replay(ReplayMessage::TogglePlay)),
+            button(">").on_press_maybe(
+                (!viewer.is_finished()).then_some(Message::Replay(ReplayMessage::StepForward))
+            ),
+        ]
+        .spacing(5.0);
+
+        let speeds = row(SPEEDS.iter().map(|&speed| {
+            button(Text::new(format!("{speed}x")))
+                .on_press_maybe(
+                    (speed != self.replay_speed)
+                        .then_some(Message::Replay(ReplayMessage::Speed(speed))),
+                )
+                .into()
+        }))
+        .spacing(5.0);
+
+        container(column![
+            Text::new("replay:"),
+            Text::new(format!(
+                "Action {} of {}, turn {} of {}",
+                viewer.step(),
+                replay.actions.len(),
+                battle.turn() + 1,
+                turns + 1
+            )),
+            Space::with_height(10.0),
+            steps,
+            Space::with_height(10.0),
+            row![Text::new("Speed:"), speeds]
+                .spacing(5.0)
+                .align_y(Alignment::Center),
+            Space::with_height(10.0),
+            Text::new("Jump to turn:"),
+            slider(0..=turns as u32, battle.turn() as u32, |turn| {
+                Message::Replay(ReplayMessage::JumpToTurn(turn as usize))
+            }),
+            Space::with_height(Length::Fill),
+            button("Close Replay").on_press(Message::Replay(ReplayMessage::Close)),
+        ])
+        .padding(10.0)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
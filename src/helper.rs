@@ -13,6 +13,17 @@ pub const fn from_rgb8(r: u8, g: u8, b: u8) -> Color {
     from_rgba8(r, g, b, 1.0)
 }
 
+///`color` moved `amount` of the way towards `other`
+pub fn mix(color: Color, other: Color, amount: f32) -> Color {
+    let amount = amount.clamp(0.0, 1.0);
+    Color {
+        r: color.r + (other.r - color.r) * amount,
+        g: color.g + (other.g - color.g) * amount,
+        b: color.b + (other.b - color.b) * amount,
+        a: color.a + (other.a - color.a) * amount,
+    }
+}
+
 pub trait If
 where
     Self: Sized,
@@ -29,4 +40,3 @@ impl<T> If for T {
         }
     }
 }
-